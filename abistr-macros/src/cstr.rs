@@ -8,6 +8,8 @@ pub(super) trait Unit : From<u8> {
     fn name() -> &'static str;
     fn into_ts(units: &[Self], s: Span) -> TokenStream;
     fn extend(units: &mut Vec<Self>, ch: char);
+    /// If `true`, `'\0'` is passed to `extend` instead of being rejected (and byte strings are forbidden.)
+    fn encodes_nul() -> bool { false }
 }
 
 impl Unit for u8 {
@@ -82,7 +84,35 @@ impl Unit for u32 {
     }
 }
 
-pub(super) fn cstr_impl<U: Unit>(input: TokenStream) -> TokenStream {
+/// Java's "Modified UTF-8": `'\0'` is encoded as `C0 80`, and supplementary characters as 3-byte surrogates.
+pub(super) struct Mutf8(u8);
+
+impl From<u8> for Mutf8 { fn from(b: u8) -> Self { Self(b) } }
+
+impl Unit for Mutf8 {
+    fn name() -> &'static str { "u8" }
+
+    fn into_ts(units: &[Self], s: Span) -> TokenStream {
+        u8::into_ts(&units.iter().map(|u| u.0).collect::<Vec<u8>>(), s)
+    }
+
+    fn extend(units: &mut Vec<Self>, ch: char) {
+        if ch == '\0' { return units.extend([0xC0, 0x80].iter().copied().map(Mutf8)) }
+        let mut buf = [0, 0];
+        for unit in ch.encode_utf16(&mut buf).iter().copied() {
+            match unit {
+                0x0000 ..= 0x007F   => units.push(Mutf8(unit as u8)),
+                0x0080 ..= 0x07FF   => units.extend([0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8].iter().copied().map(Mutf8)),
+                _                   => units.extend([0xE0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8].iter().copied().map(Mutf8)),
+            }
+        }
+    }
+
+    fn encodes_nul() -> bool { true }
+}
+
+/// `utf`: the name of a validated pointer type (e.g. `CUtf8NonNull`) to create instead of a `CStrNonNull`, forbidding byte strings.
+pub(super) fn cstr_impl<U: Unit>(input: TokenStream, utf: Option<&str>) -> TokenStream {
     let mut input = input.into_iter();
 
     let crate_ = match input.next() {
@@ -113,6 +143,11 @@ pub(super) fn cstr_impl<U: Unit>(input: TokenStream) -> TokenStream {
         None        => return compile_error("expected string argument to cstr!() macro", Span::call_site()).into(),
     };
 
+    let text = literal.to_string();
+    if (utf.is_some() || U::encodes_nul()) && (text.starts_with('b') || text.starts_with("rb")) {
+        return compile_error("byte strings may not be valid unicode, and are thus forbidden", literal.span()).into();
+    }
+
     let parsed_literal = match parse_str::<U>(&literal) {
         Ok(r) => r,
         Err(err) => return err,
@@ -121,20 +156,30 @@ pub(super) fn cstr_impl<U: Unit>(input: TokenStream) -> TokenStream {
     let s = literal.span();
     let mut o = TokenStream::new();
     o.extend(crate_);
+    if let Some(utf) = utf {
+        o.extend(vec![
+            ttp(':', Spacing::Joint, s),
+            ttp(':', Spacing::Joint, s),
+            ttid(utf, s),
+        ]);
+    } else {
+        o.extend(vec![
+            ttp(':', Spacing::Joint, s),
+            ttp(':', Spacing::Joint, s),
+            ttid("CStrNonNull", s),
+            ttp(':', Spacing::Joint, s),
+            ttp(':', Spacing::Joint, s),
+            ttp('<', Spacing::Joint, s),
+            ttid(U::name(), s),
+            ttp('>', Spacing::Joint, s),
+        ]);
+    }
     o.extend(vec![
-        ttp(':', Spacing::Joint, s),
-        ttp(':', Spacing::Joint, s),
-        ttid("CStrNonNull", s),
-        ttp(':', Spacing::Joint, s),
-        ttp(':', Spacing::Joint, s),
-        ttp('<', Spacing::Joint, s),
-        ttid(U::name(), s),
-        ttp('>', Spacing::Joint, s),
         ttp(':', Spacing::Joint, s),
         ttp(':', Spacing::Joint, s),
         ttid("zzz_unsound_do_not_call_this_directly_from_macro_units_with_nul", s),
         ttg(Delimiter::Parenthesis, s, parsed_literal)
-    ].into_iter());
+    ]);
 
     o
 }
@@ -143,12 +188,12 @@ fn parse_str<U: Unit>(literal: &Literal) -> Result<TokenStream, TokenStream> {
     let s = literal.span();
 
     let literal = literal.to_string();
-    let (byte, raw, mut literal) = if literal.starts_with("rb") || literal.starts_with("br") {
-        (true, true, &literal[2..])
-    } else if literal.starts_with("r") {
-        (false, true, &literal[1..])
-    } else if literal.starts_with("b") {
-        (true, false, &literal[1..])
+    let (byte, raw, mut literal) = if let Some(l) = literal.strip_prefix("rb").or_else(|| literal.strip_prefix("br")) {
+        (true, true, l)
+    } else if let Some(l) = literal.strip_prefix('r') {
+        (false, true, l)
+    } else if let Some(l) = literal.strip_prefix('b') {
+        (true, false, l)
     } else {
         (false, false, &literal[..])
     };
@@ -167,6 +212,7 @@ fn parse_str<U: Unit>(literal: &Literal) -> Result<TokenStream, TokenStream> {
         match ch {
             '\\' if !raw => {
                 match chars.next() {
+                    Some('0') if U::encodes_nul() => U::extend(&mut units, '\0'),
                     Some('0')  => Err(compile_error("interior `\0` not permitted in C string", s))?,
                     Some('t')  => units.push(U::from(b'\t')),
                     Some('n')  => units.push(U::from(b'\n')),
@@ -185,7 +231,10 @@ fn parse_str<U: Unit>(literal: &Literal) -> Result<TokenStream, TokenStream> {
                                 _                   => Err(compile_error("expected two hexidecimal characters after `\\x` escape sequence", s))?,
                             };
                         }
-                        if v == 0 {
+                        if v == 0 && U::encodes_nul() {
+                            U::extend(&mut units, '\0');
+                            continue;
+                        } else if v == 0 {
                             Err(compile_error("interior `\0` not permitted in C string", s))?
                         } else if core::mem::size_of::<U>() != 1 {
                             Err(compile_error("`\\x` escape sequences are ambiguous - and thus forbidden - inside unicode strings (should it be 1 byte? 1 code unit? 2 hex values? 4 hex values?)", s))?
@@ -208,7 +257,7 @@ fn parse_str<U: Unit>(literal: &Literal) -> Result<TokenStream, TokenStream> {
                                 _                           => Err(compile_error("expected 1-6 hexidecimal characters in `\\u{...}` escape sequence", s))?,
                             };
                         }
-                        if v == 0 { Err(compile_error("interior `\0` not permitted in C string", s))? }
+                        if v == 0 && !U::encodes_nul() { Err(compile_error("interior `\0` not permitted in C string", s))? }
                         let ch = char::try_from(v).map_err(|_| compile_error(format!("invalid unicode codepoint U+{:04X} in `\\u{{...}}` escape sequence", v), s))?;
                         U::extend(&mut units, ch);
                     },
@@ -217,7 +266,7 @@ fn parse_str<U: Unit>(literal: &Literal) -> Result<TokenStream, TokenStream> {
                 }
             },
             ch => {
-                if ch == '\0' { Err(compile_error("interior `\0` not permitted in C string", s))? }
+                if ch == '\0' && !U::encodes_nul() { Err(compile_error("interior `\0` not permitted in C string", s))? }
                 U::extend(&mut units, ch);
             },
         }
@@ -237,7 +286,7 @@ fn ttp(ch: char, spacing: Spacing, span: Span) -> TokenTree {
 }
 
 fn ttg(delimiter: Delimiter, span: Span, tts: impl IntoIterator<Item = TokenTree>) -> TokenTree {
-    let mut o = Group::new(delimiter, TokenStream::from_iter(tts.into_iter()));
+    let mut o = Group::new(delimiter, TokenStream::from_iter(tts));
     o.set_span(span);
     o.into()
 }
@@ -248,7 +297,7 @@ fn tts(str: impl AsRef<str>, span: Span) -> TokenTree {
     o.into()
 }
 
-fn compile_error(error: impl AsRef<str>, s: Span) -> TokenTree {
+pub(crate) fn compile_error(error: impl AsRef<str>, s: Span) -> TokenTree {
     ttg(Delimiter::None, s, vec![
         ttid("core", s),
         ttp(':', Spacing::Joint, s),
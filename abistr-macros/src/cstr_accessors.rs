@@ -0,0 +1,88 @@
+//! `#[derive(CStrAccessors)]`
+
+use crate::parse::*;
+use proc_macro::TokenStream;
+
+
+
+pub(crate) fn derive(input: TokenStream) -> TokenStream {
+    match try_derive(input) {
+        Ok(o)       => o,
+        Err(err)    => err,
+    }
+}
+
+fn try_derive(input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let s = parse_struct(input)?;
+    let krate = s.krate();
+
+    let mut methods = String::new();
+    for field in s.fields.iter() {
+        let get = attr(&field.attrs, "get");
+        let set = attr(&field.attrs, "set");
+        if get.is_none() && set.is_none() { continue }
+
+        let name = match attr(&field.attrs, "rename") {
+            Some(rename) => rename.value.clone().ok_or_else(|| error("expected `rename = \"...\"`", rename.span))?,
+            None if s.tuple => return Err(error("tuple struct fields require `#[abistr(rename = \"...\")]` to name their accessors", field.span)),
+            None => field.name.clone(),
+        };
+        let (access, ty) = (field.access(), &field.ty);
+
+        if let Some(get) = get {
+            methods += &match get.value.as_deref().unwrap_or("lossy") {
+                "lossy" => format!(
+                    "/// Get `{field}` as a string, replacing invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.\n\
+                    pub fn {name}(&self) -> {krate}::__derive::Cow<'_, str> {{ {krate}::__derive::to_string_lossy(&{access}) }}\n",
+                    field = field.name, name = name, krate = krate, access = access,
+                ),
+                "str" => format!(
+                    "/// Get `{field}` as a `&str`, or an error if it isn't valid UTF8.\n\
+                    pub fn {name}(&self) -> ::core::result::Result<&str, {krate}::__derive::Utf8Error> {{ {krate}::__derive::to_str(&{access}) }}\n",
+                    field = field.name, name = name, krate = krate, access = access,
+                ),
+                "display" => format!(
+                    "/// Get a `Display`able adapter for `{field}`, which decodes without allocating.\n\
+                    pub fn {name}(&self) -> {krate}::CStrDisplay<'_, <{ty} as {krate}::__derive::CStrField>::Unit> {{ {krate}::__derive::display(&{access}) }}\n",
+                    field = field.name, name = name, krate = krate, access = access, ty = ty,
+                ),
+                other => return Err(error(format!("unknown `get = \"{}\"`, expected one of: lossy, str, display", other), get.span)),
+            };
+        }
+
+        if let Some(set) = set {
+            let (method, doc) = match set.value.as_deref().unwrap_or("truncate") {
+                "truncate"  => ("set_str_truncate", "truncating it (on a character boundary) and returning an error if it contains a `\\0` or doesn't fit"),
+                "checked"   => ("try_set_str", "returning an error without modifying `self` if it contains a `\\0` or doesn't fit"),
+                other       => return Err(error(format!("unknown `set = \"{}\"`, expected one of: truncate, checked", other), set.span)),
+            };
+            methods += &format!(
+                "/// Set `{field}` to `value` + `\\0`, {doc}.\n\
+                pub fn set_{name}(&mut self, value: &str) -> ::core::result::Result<(), {krate}::SetStrError> {{ {access}.{method}(value) }}\n",
+                field = field.name, doc = doc, name = name, krate = krate, access = access, method = method,
+            );
+        }
+    }
+
+    let mut o = String::new();
+    if !methods.is_empty() { o += &s.impl_block(None, &methods); }
+
+    if attr(&s.attrs, "debug").is_some() {
+        let mut body = if s.tuple {
+            format!("fmt.debug_tuple({:?})", s.name)
+        } else {
+            format!("fmt.debug_struct({:?})", s.name)
+        };
+        for field in s.fields.iter() {
+            let is_str = attr(&field.attrs, "get").is_some() || attr(&field.attrs, "set").is_some();
+            let value = if is_str { format!("&{}::__derive::display(&{})", krate, field.access()) } else { format!("&{}", field.access()) };
+            body += &if s.tuple { format!(".field({})", value) } else { format!(".field({:?}, {})", field.name, value) };
+        }
+        body += ".finish()";
+        o += &s.impl_block(Some("::core::fmt::Debug"), &format!(
+            "fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {{ {} }}", body
+        ));
+    }
+
+    o.parse().map_err(|_| error("#[derive(CStrAccessors)] generated invalid code", proc_macro::Span::call_site()))
+}
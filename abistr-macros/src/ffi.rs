@@ -0,0 +1,109 @@
+//! `#[derive(IntoFfi)]`, `#[derive(FromFfi)]`
+
+use crate::parse::*;
+use proc_macro::{TokenStream, Span};
+
+
+
+pub(crate) fn derive_into(input: TokenStream) -> TokenStream {
+    match try_derive_into(input) {
+        Ok(o)       => o,
+        Err(err)    => err,
+    }
+}
+
+pub(crate) fn derive_from(input: TokenStream) -> TokenStream {
+    match try_derive_from(input) {
+        Ok(o)       => o,
+        Err(err)    => err,
+    }
+}
+
+fn try_derive_into(input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let s = parse_struct(input)?;
+    let krate = s.krate();
+    let (ffi, path) = ffi(&s, "IntoFfi")?;
+
+    let mut fields = String::new();
+    for field in s.fields.iter().filter(|f| attr(&f.attrs, "skip").is_none()) {
+        fields += &format!(
+            "{c_name}: unsafe {{ {krate}::__derive::IntoFfiField::to_ffi_field(&{access}, &mut storage, {truncate}) }}.map_err(|kind| {krate}::__derive::into_ffi_error({name:?}, kind))?, ",
+            c_name = c_name(field)?, krate = krate, access = field.access(), truncate = attr(&field.attrs, "truncate").is_some(), name = field.name,
+        );
+    }
+    let default = if attr(&s.attrs, "default").is_some() { "..::core::default::Default::default()" } else { "" };
+
+    let o = s.impl_block(Some(&format!("{}::IntoFfi", krate)), &format!(
+        "type Ffi<'s> = {ffi}; \
+        #[allow(unused_mut)] fn to_ffi(&self) -> ::core::result::Result<{krate}::FfiGuard<Self>, {krate}::IntoFfiError> {{ \
+            let mut storage = {krate}::__derive::FfiStorage::default(); \
+            let raw : <Self as {krate}::IntoFfi>::Ffi<'static> = {path} {{ {fields} {default} }}; \
+            Ok({krate}::__derive::ffi_guard(raw, storage)) \
+        }} \
+        fn shorten<'a>(raw: &'a <Self as {krate}::IntoFfi>::Ffi<'static>) -> &'a <Self as {krate}::IntoFfi>::Ffi<'a> {{ raw }}",
+        ffi = ffi, krate = krate, path = path, fields = fields, default = default,
+    ));
+
+    o.parse().map_err(|_| error("#[derive(IntoFfi)] generated invalid code", Span::call_site()))
+}
+
+fn try_derive_from(input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let s = parse_struct(input)?;
+    let krate = s.krate();
+    let (ffi, _path) = ffi(&s, "FromFfi")?;
+    let default_strict = strict(attr(&s.attrs, "get"), false)?;
+
+    let mut fields = String::new();
+    for field in s.fields.iter() {
+        fields += &if attr(&field.attrs, "skip").is_some() {
+            format!("{name}: ::core::default::Default::default(), ", name = field.name)
+        } else {
+            format!(
+                "{name}: {krate}::__derive::FromFfiField::from_ffi_field(&raw.{c_name}, {strict}).map_err(|kind| {krate}::__derive::from_ffi_error({name:?}, kind))?, ",
+                name = field.name, krate = krate, c_name = c_name(field)?, strict = strict(attr(&field.attrs, "get"), default_strict)?,
+            )
+        };
+    }
+
+    let o = s.impl_block(Some(&format!("{}::FromFfi", krate)), &format!(
+        "type Ffi<'s> = {ffi}; \
+        fn from_ffi(raw: &<Self as {krate}::FromFfi>::Ffi<'_>) -> ::core::result::Result<Self, {krate}::FromFfiError> {{ \
+            Ok(Self {{ {fields} }}) \
+        }}",
+        ffi = ffi, krate = krate, fields = fields,
+    ));
+
+    o.parse().map_err(|_| error("#[derive(FromFfi)] generated invalid code", Span::call_site()))
+}
+
+/// `#[abistr(ffi = "path::Raw<'s>")]` → `("path::Raw<'s>", "path::Raw")`
+fn ffi(s: &Struct, derive: &str) -> Result<(String, String), TokenStream> {
+    if s.tuple { return Err(error(format!("#[derive({})] only supports structs with named fields", derive), Span::call_site())) }
+    let ffi = match attr(&s.attrs, "ffi") {
+        Some(Attr { value: Some(ffi), .. }) => ffi.clone(),
+        Some(ffi)   => return Err(error("expected `ffi = \"RawType<'s>\"`", ffi.span)),
+        None        => return Err(error(format!("#[derive({})] requires `#[abistr(ffi = \"RawType<'s>\")]` on the struct", derive), Span::call_site())),
+    };
+    let path = ffi.split('<').next().unwrap_or_default().trim().to_string();
+    Ok((ffi, path))
+}
+
+/// The name of the C struct's field, overridable with `#[abistr(rename = "...")]`.
+fn c_name(field: &Field) -> Result<String, TokenStream> {
+    match attr(&field.attrs, "rename") {
+        Some(Attr { value: Some(rename), .. })  => Ok(rename.clone()),
+        Some(rename)                            => Err(error("expected `rename = \"...\"`", rename.span)),
+        None                                    => Ok(field.name.clone()),
+    }
+}
+
+/// `#[abistr(get = "str")]` → `true`, `#[abistr(get = "lossy")]` → `false`
+fn strict(get: Option<&Attr>, default: bool) -> Result<bool, TokenStream> {
+    match get {
+        None                                        => Ok(default),
+        Some(Attr { value: None, .. })              => Ok(false),
+        Some(Attr { value: Some(v), .. }) if v == "lossy"   => Ok(false),
+        Some(Attr { value: Some(v), .. }) if v == "str"     => Ok(true),
+        Some(Attr { value: Some(v), span, .. })     => Err(error(format!("unknown `get = \"{}\"`, expected one of: lossy, str", v), *span)),
+    }
+}
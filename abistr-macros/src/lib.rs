@@ -1,7 +1,26 @@
 extern crate proc_macro;
 
 mod cstr;
+mod cstr_accessors;
+mod ffi;
+mod parse;
+mod validate_cstr;
 
-#[proc_macro] pub fn cstr8_impl( input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u8 >(input) }
-#[proc_macro] pub fn cstr16_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u16>(input) }
-#[proc_macro] pub fn cstr32_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u32>(input) }
+#[proc_macro] pub fn cstr8_impl( input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u8 >(input, None) }
+#[proc_macro] pub fn cstr16_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u16>(input, None) }
+#[proc_macro] pub fn cstr32_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u32>(input, None) }
+#[proc_macro] pub fn cstr_mutf8_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<cstr::Mutf8>(input, None) }
+#[proc_macro] pub fn cutf8_impl( input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u8 >(input, Some("CUtf8NonNull" )) }
+#[proc_macro] pub fn cutf16_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr::cstr_impl::<u16>(input, Some("CUtf16NonNull")) }
+
+/// Generate string getters/setters (and optionally [`Debug`](core::fmt::Debug)) for a struct's C string fields.  See `abistr::CStrAccessors`.
+#[proc_macro_derive(CStrAccessors, attributes(abistr))] pub fn derive_cstr_accessors(input: proc_macro::TokenStream) -> proc_macro::TokenStream { cstr_accessors::derive(input) }
+
+/// Implement `abistr::ValidateCStr` by recursing through every field not marked `#[abistr(skip)]`.
+#[proc_macro_derive(ValidateCStr, attributes(abistr))] pub fn derive_validate_cstr(input: proc_macro::TokenStream) -> proc_macro::TokenStream { validate_cstr::derive(input) }
+
+/// Implement `abistr::IntoFfi`, converting each field not marked `#[abistr(skip)]` to the same-named field of `#[abistr(ffi = "...")]`.
+#[proc_macro_derive(IntoFfi, attributes(abistr))] pub fn derive_into_ffi(input: proc_macro::TokenStream) -> proc_macro::TokenStream { ffi::derive_into(input) }
+
+/// Implement `abistr::FromFfi`, converting each field not marked `#[abistr(skip)]` from the same-named field of `#[abistr(ffi = "...")]`.
+#[proc_macro_derive(FromFfi, attributes(abistr))] pub fn derive_from_ffi(input: proc_macro::TokenStream) -> proc_macro::TokenStream { ffi::derive_from(input) }
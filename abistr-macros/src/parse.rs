@@ -0,0 +1,246 @@
+//! Hand rolled parsing of `struct` definitions for `#[derive(...)]`s (no `syn` dependency.)
+
+use crate::cstr::compile_error;
+use proc_macro::{TokenStream, TokenTree, Delimiter, Spacing, Span};
+
+use core::iter::FromIterator;
+
+
+
+/// Every key that any of our derives accepts inside `#[abistr(...)]`.  Each derive ignores keys meant for the others.
+const KNOWN_KEYS : &[&str] = &["crate", "debug", "default", "ffi", "get", "set", "rename", "skip", "truncate"];
+
+pub(crate) struct Struct {
+    pub name:           String,
+    pub attrs:          Vec<Attr>,
+    pub impl_generics:  String,
+    pub ty_generics:    String,
+    pub where_clause:   String,
+    pub tuple:          bool,
+    pub fields:         Vec<Field>,
+}
+
+pub(crate) struct Field {
+    /// The identifier (or index, for tuple structs) of the field.
+    pub name:   String,
+    pub ty:     String,
+    pub attrs:  Vec<Attr>,
+    pub span:   Span,
+}
+
+pub(crate) struct Attr {
+    pub key:    String,
+    pub value:  Option<String>,
+    pub span:   Span,
+}
+
+impl Struct {
+    /// The path to the `abistr` crate, overridable with `#[abistr(crate = "...")]`.
+    pub fn krate(&self) -> String {
+        self.attrs.iter().find(|a| a.key == "crate").and_then(|a| a.value.clone()).unwrap_or_else(|| "::abistr".into())
+    }
+
+    /// `impl<...> {trait_} for Name<...> where ... { {body} }`
+    pub fn impl_block(&self, trait_: Option<&str>, body: &str) -> String {
+        let for_ = trait_.map_or(String::new(), |t| format!("{} for", t));
+        format!("impl{} {} {}{} {} {{ {} }}", self.impl_generics, for_, self.name, self.ty_generics, self.where_clause, body)
+    }
+}
+
+impl Field {
+    /// `self.field` or `self.0`
+    pub fn access(&self) -> String { format!("self.{}", self.name) }
+}
+
+pub(crate) fn attr<'a>(attrs: &'a [Attr], key: &str) -> Option<&'a Attr> { attrs.iter().find(|a| a.key == key) }
+
+pub(crate) fn parse_struct(input: TokenStream) -> Result<Struct, TokenStream> {
+    let mut tokens = input.into_iter().peekable();
+    let mut attrs = Vec::new();
+
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => match tokens.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => parse_attr(g.stream(), &mut attrs)?,
+                _ => return Err(error("expected `[...]` after `#`", p.span())),
+            },
+            Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                if let Some(TokenTree::Group(g)) = tokens.peek() { if g.delimiter() == Delimiter::Parenthesis { tokens.next(); } }
+            },
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => break,
+            Some(TokenTree::Ident(i)) if i.to_string() == "enum" || i.to_string() == "union" => {
+                return Err(error(format!("this derive only supports structs, not `{}`s", i), i.span()));
+            },
+            Some(tt)    => return Err(error(format!("expected `struct`, got `{}`", tt), tt.span())),
+            None        => return Err(error("expected `struct`", Span::call_site())),
+        }
+    }
+
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        Some(tt)    => return Err(error(format!("expected struct name, got `{}`", tt), tt.span())),
+        None        => return Err(error("expected struct name", Span::call_site())),
+    };
+
+    let mut generics = Vec::new();
+    if let Some(TokenTree::Punct(p)) = tokens.peek() {
+        if p.as_char() == '<' {
+            tokens.next();
+            let mut depth = 1;
+            let mut prev_minus = false;
+            for tt in tokens.by_ref() {
+                if let TokenTree::Punct(p) = &tt {
+                    match p.as_char() {
+                        '<'                 => depth += 1,
+                        '>' if !prev_minus  => depth -= 1,
+                        _                   => {},
+                    }
+                    if depth == 0 { break }
+                    prev_minus = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+                } else {
+                    prev_minus = false;
+                }
+                generics.push(tt);
+            }
+        }
+    }
+    let (impl_generics, ty_generics) = split_generics(generics);
+
+    // `struct S<T> where T: ... { ... }` or `struct S<T>(...) where T: ...;`
+    let mut rest = tokens.filter(|tt| !matches!(tt, TokenTree::Punct(p) if p.as_char() == ';')).collect::<Vec<_>>();
+    let body = match rest.iter().position(|tt| matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace)) {
+        Some(brace) => rest.remove(brace),
+        None if matches!(rest.first(), Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis) => rest.remove(0),
+        None => return Err(error("expected struct body", Span::call_site())),
+    };
+    let body = match body { TokenTree::Group(g) => g, _ => unreachable!() };
+    let where_clause = rest;
+    let tuple = body.delimiter() == Delimiter::Parenthesis;
+
+    let mut fields = Vec::new();
+    for (index, field) in split_top_level(body.stream(), ',').into_iter().enumerate() {
+        let span = field.first().map_or(body.span(), |tt| tt.span());
+        let mut field = field.into_iter().peekable();
+        let mut attrs = Vec::new();
+        loop {
+            match field.peek() {
+                Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                    field.next();
+                    if let Some(TokenTree::Group(g)) = field.next() { parse_attr(g.stream(), &mut attrs)?; }
+                },
+                Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                    field.next();
+                    if let Some(TokenTree::Group(g)) = field.peek() { if g.delimiter() == Delimiter::Parenthesis { field.next(); } }
+                },
+                _ => break,
+            }
+        }
+        let name = if tuple {
+            index.to_string()
+        } else {
+            let name = match field.next() {
+                Some(TokenTree::Ident(i)) => i.to_string(),
+                _ => return Err(error("expected field name", span)),
+            };
+            match field.next() {
+                Some(TokenTree::Punct(p)) if p.as_char() == ':' => {},
+                _ => return Err(error("expected `:` after field name", span)),
+            }
+            name
+        };
+        let ty = TokenStream::from_iter(field).to_string();
+        fields.push(Field { name, ty, attrs, span });
+    }
+
+    Ok(Struct { name, attrs, impl_generics, ty_generics, where_clause: TokenStream::from_iter(where_clause).to_string(), tuple, fields })
+}
+
+/// Parse the contents of `#[...]`, appending the items of any `#[abistr(...)]` to `attrs`.
+fn parse_attr(attr: TokenStream, attrs: &mut Vec<Attr>) -> Result<(), TokenStream> {
+    let mut attr = attr.into_iter();
+    match attr.next() {
+        Some(TokenTree::Ident(i)) if i.to_string() == "abistr" => {},
+        _ => return Ok(()), // doc comment, #[repr(C)], etc.
+    }
+    let items = match attr.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g.stream(),
+        _ => return Err(error("expected `#[abistr(...)]`", Span::call_site())),
+    };
+
+    for item in split_top_level(items, ',') {
+        let mut item = item.into_iter();
+        let (key, span) = match item.next() {
+            Some(TokenTree::Ident(i)) => (i.to_string(), i.span()),
+            Some(tt) => return Err(error(format!("expected identifier in `#[abistr(...)]`, got `{}`", tt), tt.span())),
+            None => continue,
+        };
+        if !KNOWN_KEYS.contains(&key.as_str()) {
+            return Err(error(format!("unknown `#[abistr(...)]` key `{}`, expected one of: {}", key, KNOWN_KEYS.join(", ")), span));
+        }
+        let value = match item.next() {
+            None => None,
+            Some(TokenTree::Punct(p)) if p.as_char() == '=' => match item.next() {
+                Some(TokenTree::Literal(l)) => {
+                    let l = l.to_string();
+                    Some(l.strip_prefix('"').and_then(|l| l.strip_suffix('"')).map_or(l.clone(), String::from))
+                },
+                Some(TokenTree::Ident(i)) => Some(i.to_string()),
+                _ => return Err(error(format!("expected a value after `{} =`", key), span)),
+            },
+            Some(tt) => return Err(error(format!("expected `=` or `,` after `{}`, got `{}`", key, tt), tt.span())),
+        };
+        attrs.push(Attr { key, value, span });
+    }
+    Ok(())
+}
+
+/// Split `<'a, T: Trait = Default, const N: usize>`'s contents into `("<'a, T: Trait, const N: usize>", "<'a, T, N>")`
+fn split_generics(generics: Vec<TokenTree>) -> (String, String) {
+    if generics.is_empty() { return (String::new(), String::new()) }
+
+    let mut impl_generics = Vec::new();
+    let mut ty_generics = Vec::new();
+    for param in split_top_level(TokenStream::from_iter(generics), ',') {
+        let param = split_top_level(TokenStream::from_iter(param), '=').into_iter().next().unwrap_or_default();
+        let name = match param.first() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '\'' => TokenStream::from_iter(param.iter().take(2).cloned()).to_string(),
+            Some(TokenTree::Ident(i)) if i.to_string() == "const" => param.get(1).map_or(String::new(), |n| n.to_string()),
+            Some(tt) => tt.to_string(),
+            None => continue,
+        };
+        impl_generics.push(TokenStream::from_iter(param).to_string());
+        ty_generics.push(name);
+    }
+    (format!("<{}>", impl_generics.join(", ")), format!("<{}>", ty_generics.join(", ")))
+}
+
+/// Split `tokens` on `sep`, ignoring any `sep`s nested inside `<...>`.
+fn split_top_level(tokens: TokenStream, sep: char) -> Vec<Vec<TokenTree>> {
+    let mut o = vec![Vec::new()];
+    let mut depth = 0;
+    let mut prev_minus = false;
+    for tt in tokens {
+        if let TokenTree::Punct(p) = &tt {
+            let ch = p.as_char();
+            match ch {
+                '<'                 => depth += 1,
+                '>' if !prev_minus  => depth -= 1,
+                _                   => {},
+            }
+            prev_minus = ch == '-' && p.spacing() == Spacing::Joint;
+            if ch == sep && depth == 0 && !(sep == '=' && p.spacing() == Spacing::Joint) {
+                o.push(Vec::new());
+                continue;
+            }
+        } else {
+            prev_minus = false;
+        }
+        o.last_mut().unwrap().push(tt);
+    }
+    if matches!(o.last(), Some(l) if l.is_empty()) { o.pop(); }
+    o
+}
+
+pub(crate) fn error(message: impl AsRef<str>, span: Span) -> TokenStream {
+    TokenStream::from(compile_error(message, span))
+}
@@ -0,0 +1,33 @@
+//! `#[derive(ValidateCStr)]`
+
+use crate::parse::*;
+use proc_macro::TokenStream;
+
+
+
+pub(crate) fn derive(input: TokenStream) -> TokenStream {
+    match try_derive(input) {
+        Ok(o)       => o,
+        Err(err)    => err,
+    }
+}
+
+fn try_derive(input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let s = parse_struct(input)?;
+    let krate = s.krate();
+
+    let mut validate = String::new();
+    let mut truncate = String::new();
+    for field in s.fields.iter().filter(|f| attr(&f.attrs, "skip").is_none()) {
+        validate += &format!("{krate}::ValidateCStr::validate_all(&{access}).map_err(|err| err.with_field({name:?}))?; ", krate = krate, access = field.access(), name = field.name);
+        truncate += &format!("{krate}::ValidateCStr::nul_truncate_all(&mut {access}); ", krate = krate, access = field.access());
+    }
+
+    let o = s.impl_block(Some(&format!("{}::ValidateCStr", krate)), &format!(
+        "fn validate_all(&self) -> ::core::result::Result<(), {krate}::FieldError> {{ {validate} Ok(()) }} \
+        fn nul_truncate_all(&mut self) {{ {truncate} }}",
+        krate = krate, validate = validate, truncate = truncate,
+    ));
+
+    o.parse().map_err(|_| error("#[derive(ValidateCStr)] generated invalid code", proc_macro::Span::call_site()))
+}
@@ -0,0 +1,540 @@
+use crate::*;
+
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+
+use core::any::TypeId;
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Display, Formatter, Write};
+use core::iter::FusedIterator;
+
+
+
+/// Which [`Unit`] a string is encoded with, for strings whose encoding is only known at runtime.
+///
+/// | Kind              | Unit      | Encoding  |
+/// | ----------------- | --------- | --------- |
+/// | [`UnitKind::U8`]  | [`u8`]    | UTF8ish   |
+/// | [`UnitKind::U16`] | [`u16`]   | UTF16ish  |
+/// | [`UnitKind::U32`] | [`u32`]   | UTF32ish  |
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnitKind {
+    /// [`u8`] units
+    U8,
+    /// [`u16`] units
+    U16,
+    /// [`u32`] units
+    U32,
+}
+
+impl UnitKind {
+    /// The [`UnitKind`] of `U`, or [`None`] if `U` isn't one of [`u8`]/[`u16`]/[`u32`].
+    pub fn of<U: Unit>() -> Option<Self> {
+        let id = TypeId::of::<U>();
+        if      id == TypeId::of::<u8 >() { Some(UnitKind::U8 ) }
+        else if id == TypeId::of::<u16>() { Some(UnitKind::U16) }
+        else if id == TypeId::of::<u32>() { Some(UnitKind::U32) }
+        else { None }
+    }
+
+    /// The size of a single unit, in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            UnitKind::U8    => 1,
+            UnitKind::U16   => 2,
+            UnitKind::U32   => 4,
+        }
+    }
+}
+
+
+
+/// A [`CStrPtr`] of [`u8`], [`u16`], or [`u32`] units, for when the [`Unit`] is only known at runtime.
+///
+/// Unlike [`CStrPtr`], this is **not** ABI compatible with any C type.  Use [`AnyCStrPtr::from_ptr_unbounded`] to wrap a
+/// raw `*const c_void` + [`UnitKind`] pair, or [`From`] to wrap an existing [`CStrPtr`] or [`CStrNonNull`].
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// # #[cfg(feature = "alloc")] {
+/// let narrow = AnyCStrPtr::from(cstr!("plugin"));
+/// let wide   = AnyCStrPtr::from(cstr16!("plugin"));
+/// assert_eq!(narrow, wide);
+/// assert_eq!(wide.kind(), UnitKind::U16);
+/// assert_eq!(wide.to_string_lossy(), "plugin");
+/// assert_eq!(narrow.to_small_cstring::<u32>().to_units(), ['p', 'l', 'u', 'g', 'i', 'n'].map(u32::from));
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub enum AnyCStrPtr<'s> {
+    /// A string of [`u8`] units
+    U8(CStrPtr<'s, u8>),
+    /// A string of [`u16`] units
+    U16(CStrPtr<'s, u16>),
+    /// A string of [`u32`] units
+    U32(CStrPtr<'s, u32>),
+}
+
+impl<'s> AnyCStrPtr<'s> {
+    /// Convert a raw C-string of `kind` units into an [`AnyCStrPtr`].  Note that the lifetime of the returned reference is unbounded!
+    ///
+    /// ### Safety
+    /// *   `ptr` must be <code>[null]\(\)</code>, or point to a `\0`-terminated C string of `kind` units
+    /// *   `ptr` must be aligned for `kind` units
+    /// *   The underlying C-string cannot change for the duration of the lifetime `'s`.
+    /// *   The lifetime `'s` is unbounded by this fn.  Very easy to accidentally extend.  Be careful!
+    ///
+    /// [null]: core::ptr::null
+    pub unsafe fn from_ptr_unbounded(kind: UnitKind, ptr: *const c_void) -> Self {
+        debug_assert!(ptr as usize % kind.size() == 0, "Undefined Behavior: C string pointer {:p} isn't aligned for {:?} units", ptr, kind);
+        match kind {
+            UnitKind::U8    => AnyCStrPtr::U8 (CStrPtr::from_ptr_unbounded(ptr.cast())),
+            UnitKind::U16   => AnyCStrPtr::U16(CStrPtr::from_ptr_unbounded(ptr.cast())),
+            UnitKind::U32   => AnyCStrPtr::U32(CStrPtr::from_ptr_unbounded(ptr.cast())),
+        }
+    }
+
+    /// The [`UnitKind`] of this string.
+    pub fn kind(&self) -> UnitKind { self.units().kind() }
+
+    /// Treat `self` as a raw, possibly <code>[null]\(\)</code> C string of [`Self::kind`] units.
+    ///
+    /// [null]: core::ptr::null
+    pub fn as_ptr(&self) -> *const c_void {
+        match self {
+            AnyCStrPtr::U8 (s) => s.as_ptr().cast(),
+            AnyCStrPtr::U16(s) => s.as_ptr().cast(),
+            AnyCStrPtr::U32(s) => s.as_ptr().cast(),
+        }
+    }
+
+    /// Checks if `self` is <code>[null]\(\)</code>.
+    ///
+    /// [null]: core::ptr::null
+    pub fn is_null(&self) -> bool { self.as_ptr().is_null() }
+
+    /// Checks if `self` is empty (either null, or the first character is `\0`.)
+    pub fn is_empty(&self) -> bool {
+        match self {
+            AnyCStrPtr::U8 (s) => s.is_empty(),
+            AnyCStrPtr::U16(s) => s.is_empty(),
+            AnyCStrPtr::U32(s) => s.is_empty(),
+        }
+    }
+
+    /// Get `self` as a <code>[CStrPtr]\<U\></code>, or [`None`] if `U` doesn't match [`Self::kind`].
+    pub fn downcast<U: Unit>(&self) -> Option<CStrPtr<'s, U>> {
+        if UnitKind::of::<U>() != Some(self.kind()) { return None }
+        Some(unsafe { CStrPtr::from_ptr_unbounded(self.as_ptr().cast()) })
+    }
+
+    /// Decode `self` into [`char`]s, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn chars_lossy(&self) -> AnyCStrCharsLossy<'s> { self.units().chars_lossy() }
+
+    /// Convert `self` to a <code>[Cow]\<[str]\></code>, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to find the terminal `\0` and decode.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<'s, str> { self.units().to_string_lossy() }
+
+    /// Get a [`Display`]able adapter for `self`, which decodes without allocating.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn display(&self) -> AnyCStrDisplay<'s> { AnyCStrDisplay(self.units()) }
+
+    /// Transcode `self` into a <code>[SmallCString]\<U\></code>, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    /// Strings already of `U` units are copied as-is, invalid sequences included.
+    ///
+    /// `O(n)` to find the terminal `\0` and transcode.
+    #[cfg(feature = "alloc")]
+    pub fn to_small_cstring<U: Unit>(&self) -> SmallCString<U> { self.units().to_small_cstring() }
+
+    fn units(&self) -> AnyUnits<'s> {
+        match self {
+            AnyCStrPtr::U8 (s) => AnyUnits::U8 (s.to_units()),
+            AnyCStrPtr::U16(s) => AnyUnits::U16(s.to_units()),
+            AnyCStrPtr::U32(s) => AnyUnits::U32(s.to_units()),
+        }
+    }
+}
+
+impl<'s> From<CStrPtr<'s, u8 >> for AnyCStrPtr<'s> { fn from(s: CStrPtr<'s, u8 >) -> Self { AnyCStrPtr::U8 (s) } }
+impl<'s> From<CStrPtr<'s, u16>> for AnyCStrPtr<'s> { fn from(s: CStrPtr<'s, u16>) -> Self { AnyCStrPtr::U16(s) } }
+impl<'s> From<CStrPtr<'s, u32>> for AnyCStrPtr<'s> { fn from(s: CStrPtr<'s, u32>) -> Self { AnyCStrPtr::U32(s) } }
+
+impl<'s> From<CStrNonNull<'s, u8 >> for AnyCStrPtr<'s> { fn from(s: CStrNonNull<'s, u8 >) -> Self { AnyCStrPtr::U8 (unsafe { CStrPtr::from_ptr_unbounded(s.as_ptr()) }) } }
+impl<'s> From<CStrNonNull<'s, u16>> for AnyCStrPtr<'s> { fn from(s: CStrNonNull<'s, u16>) -> Self { AnyCStrPtr::U16(unsafe { CStrPtr::from_ptr_unbounded(s.as_ptr()) }) } }
+impl<'s> From<CStrNonNull<'s, u32>> for AnyCStrPtr<'s> { fn from(s: CStrNonNull<'s, u32>) -> Self { AnyCStrPtr::U32(unsafe { CStrPtr::from_ptr_unbounded(s.as_ptr()) }) } }
+
+impl Debug for AnyCStrPtr<'_> {
+    /// Formats as per the underlying [`CStrPtr`], e.g. `"example"` or `null`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AnyCStrPtr::U8 (s) => Debug::fmt(s, f),
+            AnyCStrPtr::U16(s) => Debug::fmt(s, f),
+            AnyCStrPtr::U32(s) => Debug::fmt(s, f),
+        }
+    }
+}
+
+/// Strings of the same [`UnitKind`] are equal if their units are equal.  Strings of different kinds are equal if both
+/// are well formed, and decode to the same [`char`]s.  <code>[null]\(\)</code> is equal to `""`.
+///
+/// [null]: core::ptr::null
+impl PartialEq for AnyCStrPtr<'_> { fn eq(&self, other: &Self) -> bool { self.units() == other.units() } }
+impl Eq for AnyCStrPtr<'_> {}
+
+/// Equal if `self` is well formed, and decodes to `other`.
+impl PartialEq<str> for AnyCStrPtr<'_> { fn eq(&self, other: &str) -> bool { self.units() == AnyUnits::U8(other.as_bytes()) } }
+impl PartialEq<&str> for AnyCStrPtr<'_> { fn eq(&self, other: &&str) -> bool { self.units() == AnyUnits::U8(other.as_bytes()) } }
+
+
+
+/// A [`CStrBuf`] of `N` [`u8`], [`u16`], or [`u32`] units, for when the [`Unit`] is only known at runtime.
+///
+/// Unlike [`CStrBuf`], this is **not** ABI compatible with any C type.  As with [`CStrBuf`], the buffer might not be `\0`-terminated.
+/// Also as with [`CStrBuf`], `==` between two [`AnyCStrBuf`]s compares the *entire* buffers: compare against an [`AnyCStrPtr`] or [`str`] to compare strings.
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// let mut name = AnyCStrBuf::<16>::new(UnitKind::U16);
+/// name.try_set_str("plugin").unwrap();
+/// assert_eq!(name.kind(), UnitKind::U16);
+/// assert_eq!(name, "plugin");
+/// assert_eq!(name.display().to_string(), "plugin");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnyCStrBuf<const N: usize> {
+    /// A buffer of [`u8`] units
+    U8(CStrBuf<u8, N>),
+    /// A buffer of [`u16`] units
+    U16(CStrBuf<u16, N>),
+    /// A buffer of [`u32`] units
+    U32(CStrBuf<u32, N>),
+}
+
+impl<const N: usize> AnyCStrBuf<N> {
+    /// Create an empty (zeroed) buffer of `kind` units.
+    pub fn new(kind: UnitKind) -> Self {
+        match kind {
+            UnitKind::U8    => AnyCStrBuf::U8 (CStrBuf::default()),
+            UnitKind::U16   => AnyCStrBuf::U16(CStrBuf::default()),
+            UnitKind::U32   => AnyCStrBuf::U32(CStrBuf::default()),
+        }
+    }
+
+    /// The [`UnitKind`] of this buffer.
+    pub fn kind(&self) -> UnitKind { self.units().kind() }
+
+    /// Checks if `self` is empty (e.g. the first character is `\0`.)
+    pub fn is_empty(&self) -> bool {
+        match self {
+            AnyCStrBuf::U8 (s) => s.is_empty(),
+            AnyCStrBuf::U16(s) => s.is_empty(),
+            AnyCStrBuf::U32(s) => s.is_empty(),
+        }
+    }
+
+    /// Borrow `self` as a <code>[CStrBuf]\<U, N\></code>, or [`None`] if `U` doesn't match [`Self::kind`].
+    pub fn downcast<U: Unit>(&self) -> Option<&CStrBuf<U, N>> {
+        if UnitKind::of::<U>() != Some(self.kind()) { return None }
+        let buf : *const CStrBuf<U, N> = match self {
+            AnyCStrBuf::U8 (s) => (s as *const CStrBuf<u8,  N>).cast(),
+            AnyCStrBuf::U16(s) => (s as *const CStrBuf<u16, N>).cast(),
+            AnyCStrBuf::U32(s) => (s as *const CStrBuf<u32, N>).cast(),
+        };
+        Some(unsafe { &*buf })
+    }
+
+    /// Borrow the buffer as an [`AnyCStrPtr`], returning <code>[Err]\([NotNulTerminatedError]\)</code> instead if the buffer isn't `\0`-terminated.
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn as_any_cstr_ptr(&self) -> Result<AnyCStrPtr<'_>, NotNulTerminatedError> {
+        Ok(match self {
+            AnyCStrBuf::U8 (s) => AnyCStrPtr::U8 (unsafe { CStrPtr::from_units_with_nul_unchecked(s.to_units_with_nul()?) }),
+            AnyCStrBuf::U16(s) => AnyCStrPtr::U16(unsafe { CStrPtr::from_units_with_nul_unchecked(s.to_units_with_nul()?) }),
+            AnyCStrBuf::U32(s) => AnyCStrPtr::U32(unsafe { CStrPtr::from_units_with_nul_unchecked(s.to_units_with_nul()?) }),
+        })
+    }
+
+    /// Modifies the buffer to contain `data` (encoded per [`Self::kind`]) + `\0`.  Leaves the buffer untouched and returns <code>[Err]\([SetStrError]\)</code> if `data` contains a `\0` or won't fit.
+    pub fn try_set_str(&mut self, data: &str) -> Result<(), SetStrError> {
+        match self {
+            AnyCStrBuf::U8 (s) => s.try_set_str(data),
+            AnyCStrBuf::U16(s) => s.try_set_str(data),
+            AnyCStrBuf::U32(s) => s.try_set_str(data),
+        }
+    }
+
+    /// Modifies the buffer to contain `data` (encoded per [`Self::kind`]) + `\0`.
+    /// If `data` will not fit, it will be truncated - on a character boundary - with a final `\0` before returning <code>[Err]\([SetStrError::BufferTooSmall]\)</code>.
+    /// If `data` contains a `\0`, <code>[Err]\([SetStrError::InteriorNul]\)</code> will be returned without modifying the underlying buffer.
+    ///
+    /// ### Panics
+    ///
+    /// If `N == 0`
+    pub fn set_str_truncate(&mut self, data: &str) -> Result<(), SetStrError> {
+        match self {
+            AnyCStrBuf::U8 (s) => s.set_str_truncate(data),
+            AnyCStrBuf::U16(s) => s.set_str_truncate(data),
+            AnyCStrBuf::U32(s) => s.set_str_truncate(data),
+        }
+    }
+
+    /// Decode the buffer into [`char`]s, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn chars_lossy(&self) -> AnyCStrCharsLossy<'_> { self.units().chars_lossy() }
+
+    /// Convert the buffer to a <code>[Cow]\<[str]\></code>, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> { self.units().to_string_lossy() }
+
+    /// Get a [`Display`]able adapter for the buffer, which decodes without allocating.
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn display(&self) -> AnyCStrDisplay<'_> { AnyCStrDisplay(self.units()) }
+
+    /// Transcode the buffer into a <code>[SmallCString]\<U\></code>, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    /// Buffers already of `U` units are copied as-is, invalid sequences included.
+    ///
+    /// `O(n)` to locate the terminal `\0` and transcode.
+    #[cfg(feature = "alloc")]
+    pub fn to_small_cstring<U: Unit>(&self) -> SmallCString<U> { self.units().to_small_cstring() }
+
+    fn units(&self) -> AnyUnits<'_> {
+        match self {
+            AnyCStrBuf::U8 (s) => AnyUnits::U8 (s.to_units()),
+            AnyCStrBuf::U16(s) => AnyUnits::U16(s.to_units()),
+            AnyCStrBuf::U32(s) => AnyUnits::U32(s.to_units()),
+        }
+    }
+}
+
+impl<const N: usize> From<CStrBuf<u8,  N>> for AnyCStrBuf<N> { fn from(s: CStrBuf<u8,  N>) -> Self { AnyCStrBuf::U8 (s) } }
+impl<const N: usize> From<CStrBuf<u16, N>> for AnyCStrBuf<N> { fn from(s: CStrBuf<u16, N>) -> Self { AnyCStrBuf::U16(s) } }
+impl<const N: usize> From<CStrBuf<u32, N>> for AnyCStrBuf<N> { fn from(s: CStrBuf<u32, N>) -> Self { AnyCStrBuf::U32(s) } }
+
+impl<const N: usize> Debug for AnyCStrBuf<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { Debug::fmt(&self.display(), f) }
+}
+
+/// Equal if the string portion of `self` is well formed, and decodes to `other`.
+impl<const N: usize> PartialEq<str> for AnyCStrBuf<N> { fn eq(&self, other: &str) -> bool { self.units() == AnyUnits::U8(other.as_bytes()) } }
+impl<const N: usize> PartialEq<&str> for AnyCStrBuf<N> { fn eq(&self, other: &&str) -> bool { self.units() == AnyUnits::U8(other.as_bytes()) } }
+
+/// Equal per <code>[AnyCStrPtr] == [AnyCStrPtr]</code>, comparing only the string portion of the buffer.
+impl<const N: usize> PartialEq<AnyCStrPtr<'_>> for AnyCStrBuf<N> { fn eq(&self, other: &AnyCStrPtr) -> bool { self.units() == other.units() } }
+impl<const N: usize> PartialEq<AnyCStrBuf<N>> for AnyCStrPtr<'_> { fn eq(&self, other: &AnyCStrBuf<N>) -> bool { self.units() == other.units() } }
+
+
+
+/// Helper struct for printing [`AnyCStrPtr`]s and [`AnyCStrBuf`]s with [`format!`] and `{}`, per [`CStrDisplay`].
+#[derive(Clone, Copy)]
+pub struct AnyCStrDisplay<'s>(AnyUnits<'s>);
+
+impl Debug for AnyCStrDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            AnyUnits::U8 (u) => Debug::fmt(&CStrDisplay::new(u), f),
+            AnyUnits::U16(u) => Debug::fmt(&CStrDisplay::new(u), f),
+            AnyUnits::U32(u) => Debug::fmt(&CStrDisplay::new(u), f),
+        }
+    }
+}
+
+impl Display for AnyCStrDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for ch in self.0.chars_lossy() { f.write_char(ch)?; }
+        Ok(())
+    }
+}
+
+
+
+/// Iterator over the [`char`]s of an [`AnyCStrPtr`] or [`AnyCStrBuf`], replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+#[derive(Clone)]
+pub struct AnyCStrCharsLossy<'s>(AnyCharsLossy<'s>);
+
+#[derive(Clone)]
+enum AnyCharsLossy<'s> {
+    U8 (CStrCharsLossy<'s, u8 >),
+    U16(CStrCharsLossy<'s, u16>),
+    U32(CStrCharsLossy<'s, u32>),
+}
+
+impl Iterator for AnyCStrCharsLossy<'_> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        match &mut self.0 {
+            AnyCharsLossy::U8 (c) => c.next(),
+            AnyCharsLossy::U16(c) => c.next(),
+            AnyCharsLossy::U32(c) => c.next(),
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            AnyCharsLossy::U8 (c) => c.size_hint(),
+            AnyCharsLossy::U16(c) => c.size_hint(),
+            AnyCharsLossy::U32(c) => c.size_hint(),
+        }
+    }
+}
+
+impl FusedIterator for AnyCStrCharsLossy<'_> {}
+
+
+
+/// The string portion of an [`AnyCStrPtr`] / [`AnyCStrBuf`], excluding the terminal `\0`.
+#[derive(Clone, Copy)]
+enum AnyUnits<'s> {
+    U8 (&'s [u8 ]),
+    U16(&'s [u16]),
+    U32(&'s [u32]),
+}
+
+impl<'s> AnyUnits<'s> {
+    fn kind(self) -> UnitKind {
+        match self {
+            AnyUnits::U8 (_) => UnitKind::U8,
+            AnyUnits::U16(_) => UnitKind::U16,
+            AnyUnits::U32(_) => UnitKind::U32,
+        }
+    }
+
+    fn chars_lossy(self) -> AnyCStrCharsLossy<'s> {
+        AnyCStrCharsLossy(match self {
+            AnyUnits::U8 (u) => AnyCharsLossy::U8 (decode_lossy(u)),
+            AnyUnits::U16(u) => AnyCharsLossy::U16(decode_lossy(u)),
+            AnyUnits::U32(u) => AnyCharsLossy::U32(decode_lossy(u)),
+        })
+    }
+
+    #[cfg(feature = "alloc")] fn to_string_lossy(self) -> Cow<'s, str> {
+        match self {
+            AnyUnits::U8 (u) => Unit::to_string_lossy(u),
+            AnyUnits::U16(u) => Unit::to_string_lossy(u),
+            AnyUnits::U32(u) => Unit::to_string_lossy(u),
+        }
+    }
+
+    #[cfg(feature = "alloc")] fn to_small_cstring<U: Unit>(self) -> SmallCString<U> {
+        if UnitKind::of::<U>() == Some(self.kind()) {
+            let (ptr, len) = match self {
+                AnyUnits::U8 (u) => (u.as_ptr().cast(), u.len()),
+                AnyUnits::U16(u) => (u.as_ptr().cast(), u.len()),
+                AnyUnits::U32(u) => (u.as_ptr().cast(), u.len()),
+            };
+            let units : &[U] = unsafe { core::slice::from_raw_parts(ptr, len) }; // `U` is the same type as the slice's units
+            SmallCString::from_unit_iter(units.iter().copied()).expect("C strings contain no interior `\\0`s")
+        } else {
+            SmallCString::encode(&self.to_string_lossy(), NulPolicy::Error, true).expect("C strings contain no interior `\\0`s")
+        }
+    }
+}
+
+/// Compare `a` and `b` by decoded [`char`]s.  Ill-formed sequences never compare equal.
+fn eq_chars<A: Unit, B: Unit>(mut a: &[A], mut b: &[B]) -> bool {
+    while !a.is_empty() && !b.is_empty() {
+        let (ach, an) = A::decode(a);
+        let (bch, bn) = B::decode(b);
+        if ach.is_none() || ach != bch { return false }
+        a = &a[an..];
+        b = &b[bn..];
+    }
+    a.is_empty() && b.is_empty()
+}
+
+impl PartialEq for AnyUnits<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (AnyUnits::U8 (a), AnyUnits::U8 (b)) => a == b,
+            (AnyUnits::U16(a), AnyUnits::U16(b)) => a == b,
+            (AnyUnits::U32(a), AnyUnits::U32(b)) => a == b,
+            (AnyUnits::U8 (a), AnyUnits::U16(b)) => eq_chars(a, b),
+            (AnyUnits::U8 (a), AnyUnits::U32(b)) => eq_chars(a, b),
+            (AnyUnits::U16(a), AnyUnits::U8 (b)) => eq_chars(a, b),
+            (AnyUnits::U16(a), AnyUnits::U32(b)) => eq_chars(a, b),
+            (AnyUnits::U32(a), AnyUnits::U8 (b)) => eq_chars(a, b),
+            (AnyUnits::U32(a), AnyUnits::U16(b)) => eq_chars(a, b),
+        }
+    }
+}
+
+
+
+#[test] fn kinds() {
+    assert_eq!(UnitKind::of::<u8 >(), Some(UnitKind::U8 ));
+    assert_eq!(UnitKind::of::<u16>(), Some(UnitKind::U16));
+    assert_eq!(UnitKind::of::<u32>(), Some(UnitKind::U32));
+    assert_eq!(UnitKind::U16.size(), 2);
+}
+
+#[test] fn any_cstr_ptr() {
+    let narrow  = AnyCStrPtr::from(cstr!("abc"));
+    let wide    = AnyCStrPtr::from(cstr16!("abc"));
+    let wider   = unsafe { AnyCStrPtr::from_ptr_unbounded(UnitKind::U32, cstr32!("abc").as_ptr().cast()) };
+    let null    = unsafe { AnyCStrPtr::from_ptr_unbounded(UnitKind::U16, core::ptr::null()) };
+
+    assert_eq!(narrow.kind(), UnitKind::U8);
+    assert_eq!(wider.kind(), UnitKind::U32);
+    assert!(null.is_null() && null.is_empty() && !wide.is_empty());
+
+    assert_eq!(narrow, wide);
+    assert_eq!(wide, wider);
+    assert_eq!(null, AnyCStrPtr::from(cstr!("")));
+    assert_ne!(narrow, AnyCStrPtr::from(cstr16!("abcd")));
+    assert_eq!(wider, "abc");
+    assert_ne!(wider, "ab");
+
+    // ill-formed strings only equal the same units
+    let bad8    = AnyCStrPtr::from(cstr!(b"\xFF"));
+    let bad16   = AnyCStrPtr::from(CStrPtr::from_units_with_nul(&[0xD800u16, 0][..]).unwrap());
+    assert_eq!(bad8, bad8);
+    assert_ne!(bad8, bad16);
+    assert_ne!(bad16, AnyCStrPtr::from(cstr!("\u{FFFD}")));
+
+    assert_eq!(wide.downcast::<u16>().map(|s| s.to_units()), Some(&[0x61, 0x62, 0x63][..]));
+    assert!(wide.downcast::<u8>().is_none());
+    assert!(wide.chars_lossy().eq("abc".chars()));
+    assert!(bad16.chars_lossy().eq("\u{FFFD}".chars()));
+
+    #[cfg(feature = "alloc")] {
+        assert_eq!(alloc::format!("{} {:?} {:?}", wider.display(), wide, null), "abc \"abc\" null");
+        assert_eq!(bad8.to_string_lossy(), "\u{FFFD}");
+        assert_eq!(narrow.to_small_cstring::<u16>().to_units(), &[0x61, 0x62, 0x63]);
+        assert_eq!(bad8.to_small_cstring::<u8>().to_units(), b"\xFF");
+        assert_eq!(bad16.to_small_cstring::<u8>().to_units(), "\u{FFFD}".as_bytes());
+    }
+}
+
+#[test] fn any_cstr_buf() {
+    let mut buf = AnyCStrBuf::<4>::new(UnitKind::U32);
+    assert!(buf.is_empty());
+    assert_eq!(buf.try_set_str("abcd"), Err(BufferTooSmallError::new(5, 4).into()));
+    assert!(buf.is_empty());
+    assert!(buf.set_str_truncate("abcd").is_err());
+    assert_eq!(buf, "abc");
+    assert_eq!(buf, AnyCStrPtr::from(cstr!("abc")));
+    assert_eq!(buf.as_any_cstr_ptr().map(|s| s.kind()), Ok(UnitKind::U32));
+    assert_eq!(buf.downcast::<u32>().map(|b| b.to_units()), Some(&[0x61, 0x62, 0x63][..]));
+    assert!(buf.downcast::<u16>().is_none());
+
+    let unterminated = AnyCStrBuf::from(unsafe { CStrBuf::<u8, 2>::from_truncate_without_nul(b"ab") });
+    assert_eq!(unterminated.as_any_cstr_ptr(), Err(NotNulTerminatedError(())));
+    assert_eq!(unterminated, "ab");
+    assert!(unterminated.chars_lossy().eq("ab".chars()));
+
+    #[cfg(feature = "alloc")] {
+        assert_eq!(alloc::format!("{} {:?}", buf.display(), unterminated), "abc \"ab\"");
+        assert_eq!(buf.to_string_lossy(), "abc");
+        assert_eq!(buf.to_small_cstring::<u8>().to_units(), b"abc");
+    }
+}
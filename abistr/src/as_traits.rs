@@ -1,12 +1,16 @@
 use crate::{c_char, *};
 
-#[cfg(feature = "std")] use std::ffi::*;
+use core::ffi::CStr;
+#[cfg(feature = "alloc")] use alloc::ffi::CString;
 
-use core::ptr::*;
+#[cfg(doc)] use core::ptr::*;
 
 
 
-/// Treat `self` as a C-style string
+/// Treat `self` as a C-style string of [`Unit`]s.
+///
+/// The returned pointer is a <code>\*const [U::CChar](Unit::CChar)</code>: <code>\*const [c_char]</code> for [`u8`] strings, `*const u16` for [`u16`] strings, etc.
+/// String types generic over their [`Unit`] can implement this once, and code generic over encodings can take <code>impl [AsCStr]\<U\></code>.
 ///
 /// ### Safety
 ///
@@ -14,21 +18,37 @@ use core::ptr::*;
 ///
 /// *   The returned pointer points to a valid `\0`-terminated string.
 /// *   Said string remains valid and immutable until `self` is dropped or a `&mut self` method is called.
-pub unsafe trait AsCStr<C = c_char> {
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// struct Interned<U: Unit>(&'static [U]); // always `\0`-terminated
+///
+/// unsafe impl<U: Unit> AsCStr<U> for Interned<U> {
+///     fn as_cstr(&self) -> *const U::CChar { self.0.as_ptr().cast() }
+/// }
+///
+/// fn len<U: Unit>(s: impl AsCStr<U>) -> usize { unsafe { CStrNonNull::<U>::from_ptr_unchecked_unbounded(s.as_cstr()) }.to_units().len() }
+/// assert_eq!(len(Interned(b"abc\0")), 3);
+/// assert_eq!(len(Interned(&[0x61u16, 0])), 1);
+/// # fn c_api(_: impl AsOptCStr) {}
+/// c_api(Interned(b"abc\0")); // ...and AsOptCStr comes for free
+/// ```
+pub unsafe trait AsCStr<U: Unit = u8> {
     /// Returns a `\0`-terminated C string
-    fn as_cstr(&self) -> *const C;
+    fn as_cstr(&self) -> *const U::CChar;
 }
 
-unsafe impl AsCStr<i8   > for CStrNonNull<'_, u8    > { fn as_cstr(&self) -> *const i8  { self.as_ptr().cast() } }
-unsafe impl AsCStr<u8   > for CStrNonNull<'_, u8    > { fn as_cstr(&self) -> *const u8  { self.as_ptr().cast() } }
-unsafe impl AsCStr<u16  > for CStrNonNull<'_, u16   > { fn as_cstr(&self) -> *const u16 { self.as_ptr() } }
-unsafe impl AsCStr<u32  > for CStrNonNull<'_, u32   > { fn as_cstr(&self) -> *const u32 { self.as_ptr() } }
+unsafe impl<U: Unit> AsCStr<U> for CStrNonNull<'_, U> { fn as_cstr(&self) -> *const U::CChar { self.as_ptr() } }
 
-#[cfg(feature = "std")] unsafe impl AsCStr<i8> for &'_ CStr { fn as_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
-#[cfg(feature = "std")] unsafe impl AsCStr<u8> for &'_ CStr { fn as_cstr(&self) -> *const u8 { self.as_ptr().cast() } }
+unsafe impl AsCStr<u8> for &'_ CStr { fn as_cstr(&self) -> *const c_char { self.as_ptr() } }
+#[cfg(feature = "alloc")] unsafe impl AsCStr<u8> for CString { fn as_cstr(&self) -> *const c_char { self.as_ptr() } }
 
-#[cfg(feature = "std")] unsafe impl AsCStr<i8> for CString { fn as_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
-#[cfg(feature = "std")] unsafe impl AsCStr<u8> for CString { fn as_cstr(&self) -> *const u8 { self.as_ptr().cast() } }
+// `*const i8` interop for [`u8`] strings: `AsCStr<c_char>` is `AsCStr<i8>` on most platforms.
+unsafe impl AsCStr<i8> for CStrNonNull<'_, u8> { fn as_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
+unsafe impl AsCStr<i8> for &'_ CStr { fn as_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
+#[cfg(feature = "alloc")] unsafe impl AsCStr<i8> for CString { fn as_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
 
 #[cfg(feature = "widestring")] unsafe impl AsCStr<u16> for &'_ widestring::U16CStr { fn as_cstr(&self) -> *const u16 { self.as_ptr() } }
 #[cfg(feature = "widestring")] unsafe impl AsCStr<u32> for &'_ widestring::U32CStr { fn as_cstr(&self) -> *const u32 { self.as_ptr() } }
@@ -38,7 +58,9 @@ unsafe impl AsCStr<u32  > for CStrNonNull<'_, u32   > { fn as_cstr(&self) -> *co
 
 
 
-/// Treat `self` as a C-style string or <code>[null]\(\)</code>
+/// Treat `self` as a C-style string of [`Unit`]s or <code>[null]\(\)</code>
+///
+/// Anything implementing <code>[AsCStr]\<U\></code> implements this too.
 ///
 /// ### Safety
 ///
@@ -46,27 +68,13 @@ unsafe impl AsCStr<u32  > for CStrNonNull<'_, u32   > { fn as_cstr(&self) -> *co
 ///
 /// *   The returned pointer is either <code>[null]\(\)</code>, or points to a `\0`-terminated string.
 /// *   If pointing to a string, said string remains valid and immutable until `self` is dropped or a `&mut self` method is called.
-pub unsafe trait AsOptCStr<C = c_char> {
+pub unsafe trait AsOptCStr<U: Unit = u8> {
     /// Returns a `\0`-terminated C string, or <code>[null]\(\)</code>.
-    fn as_opt_cstr(&self) -> *const C;
+    fn as_opt_cstr(&self) -> *const U::CChar;
 }
 
-unsafe impl AsOptCStr<i8    > for () { fn as_opt_cstr(&self) -> *const i8     { null() } }
-unsafe impl AsOptCStr<u8    > for () { fn as_opt_cstr(&self) -> *const u8     { null() } }
-unsafe impl AsOptCStr<u16   > for () { fn as_opt_cstr(&self) -> *const u16    { null() } }
-unsafe impl AsOptCStr<u32   > for () { fn as_opt_cstr(&self) -> *const u32    { null() } }
-
-unsafe impl AsOptCStr<i8    > for CStrPtr<'_, u8 > { fn as_opt_cstr(&self) -> *const i8     { self.as_ptr().cast() } }
-unsafe impl AsOptCStr<u8    > for CStrPtr<'_, u8 > { fn as_opt_cstr(&self) -> *const u8     { self.as_ptr().cast() } }
-unsafe impl AsOptCStr<u16   > for CStrPtr<'_, u16> { fn as_opt_cstr(&self) -> *const u16    { self.as_ptr() } }
-unsafe impl AsOptCStr<u32   > for CStrPtr<'_, u32> { fn as_opt_cstr(&self) -> *const u32    { self.as_ptr() } }
-
-unsafe impl<T: AsCStr<i8    >> AsOptCStr<i8    > for T { fn as_opt_cstr(&self) -> *const i8     { self.as_cstr() } }
-unsafe impl<T: AsCStr<u8    >> AsOptCStr<u8    > for T { fn as_opt_cstr(&self) -> *const u8     { self.as_cstr() } }
-unsafe impl<T: AsCStr<u16   >> AsOptCStr<u16   > for T { fn as_opt_cstr(&self) -> *const u16    { self.as_cstr() } }
-unsafe impl<T: AsCStr<u32   >> AsOptCStr<u32   > for T { fn as_opt_cstr(&self) -> *const u32    { self.as_cstr() } }
+unsafe impl<U: Unit, T: AsCStr<U>> AsOptCStr<U> for T { fn as_opt_cstr(&self) -> *const U::CChar { self.as_cstr() } }
+unsafe impl AsOptCStr<i8> for CStrPtr<'_, u8> { fn as_opt_cstr(&self) -> *const i8 { self.as_ptr().cast() } }
 
-unsafe impl<T: AsCStr<i8    >> AsOptCStr<i8    > for Option<T> { fn as_opt_cstr(&self) -> *const i8     { self.as_ref().map_or(null(), |s| s.as_cstr()) } }
-unsafe impl<T: AsCStr<u8    >> AsOptCStr<u8    > for Option<T> { fn as_opt_cstr(&self) -> *const u8     { self.as_ref().map_or(null(), |s| s.as_cstr()) } }
-unsafe impl<T: AsCStr<u16   >> AsOptCStr<u16   > for Option<T> { fn as_opt_cstr(&self) -> *const u16    { self.as_ref().map_or(null(), |s| s.as_cstr()) } }
-unsafe impl<T: AsCStr<u32   >> AsOptCStr<u32   > for Option<T> { fn as_opt_cstr(&self) -> *const u32    { self.as_ref().map_or(null(), |s| s.as_cstr()) } }
+// `()`, `CStrPtr<U>`, `Option<T>`: see `impl_unit_conversions!` for why these aren't blanket impls.
+impl_unit_conversions!(u8, u16, u32, i8, i16, i32, Utf8, Ascii, U16Le, U16Be, U32Le, U32Be);
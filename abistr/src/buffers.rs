@@ -2,8 +2,9 @@ use crate::*;
 
 #[cfg(feature = "widestring")] use widestring::*;
 
-#[cfg(feature = "std")] use std::borrow::Cow;
-#[cfg(feature = "std")] use std::ffi::*;
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+#[cfg(feature = "alloc")] use alloc::string::String;
+use core::ffi::CStr;
 
 use core::fmt::{self, Debug, Formatter};
 use core::str::*;
@@ -39,28 +40,36 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     /// ### Panics
     ///
     /// If `self.buffer.is_empty()` (...did you create a `CStrBuf<[u8; 0]>` or something?  Weirdo.)
-    pub fn from_truncate(data: &(impl AsRef<[U]> + ?Sized)) -> Self {
+    pub fn from_truncate(data: &(impl AsRef<[U]> + ?Sized)) -> Self where U : AnyUnitSequence {
         let mut s = Self::default();
         let _ = s.set_truncate(data);
         s
     }
 
     /// Create a [`CStrBuf`] from `data` + `\0`.  Will be truncated to fit if `data` is too long.  **Not** guaranteed to be `\0`-terminated!
-    pub unsafe fn from_truncate_without_nul(data: &(impl AsRef<[U]> + ?Sized)) -> Self {
+    ///
+    /// ### Safety
+    ///
+    /// The buffer might not be `\0`-terminated afterwards.  It's up to you to ensure nothing relies on it being so.
+    pub unsafe fn from_truncate_without_nul(data: &(impl AsRef<[U]> + ?Sized)) -> Self where U : AnyUnitSequence {
         let mut s = Self::default();
         let _ = s.set_truncate_without_nul(data);
         s
     }
 
     /// Create a [`CStrBuf`] from `data` + `\0`.
-    pub fn try_from(data: &(impl AsRef<[U]> + ?Sized)) -> Result<Self, BufferTooSmallError> {
+    pub fn try_from(data: &(impl AsRef<[U]> + ?Sized)) -> Result<Self, BufferTooSmallError> where U : AnyUnitSequence {
         let mut s = Self::default();
         s.try_set(data)?;
         Ok(s)
     }
 
     /// Create a [`CStrBuf`] from `data` + `\0`.  Will succeed even if the `\0` doesn't fit.
-    pub unsafe fn try_from_without_nul(data: &(impl AsRef<[U]> + ?Sized)) -> Result<Self, BufferTooSmallError> {
+    ///
+    /// ### Safety
+    ///
+    /// The buffer might not be `\0`-terminated afterwards.  It's up to you to ensure nothing relies on it being so.
+    pub unsafe fn try_from_without_nul(data: &(impl AsRef<[U]> + ?Sized)) -> Result<Self, BufferTooSmallError> where U : AnyUnitSequence {
         let mut s = Self::default();
         s.try_set_without_nul(data)?;
         Ok(s)
@@ -70,14 +79,14 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     pub fn buffer(&self) -> &[U] { &self.buffer[..] }
 
     /// Checks if `self` is empty (e.g. the first character is `\0`.)
-    pub fn is_empty(&self) -> bool { self.buffer.iter().copied().next() == Some(private::Unit::NUL) }
+    pub fn is_empty(&self) -> bool { self.buffer.iter().copied().next() == Some(Unit::NUL) }
 
     /// Get the code units of the string portion of the buffer.  This will not contain any `\0` characters, and is not guaranteed to have a `\0` after the slice!
     ///
     /// `O(n)` to locate the terminal `\0`.
     pub fn to_units(&self) -> &[U] {
         let buffer = self.buffer();
-        match buffer.iter().copied().position(|ch| ch == private::Unit::NUL) {
+        match buffer.iter().copied().position(|ch| ch == Unit::NUL) {
             Some(nul)   => &buffer[..nul],
             None        => buffer,
         }
@@ -90,7 +99,7 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     /// `O(n)` to locate the terminal `\0`.
     pub fn to_units_with_nul(&self) -> Result<&[U], NotNulTerminatedError> {
         let buffer = self.buffer();
-        match buffer.iter().copied().position(|ch| ch == private::Unit::NUL) {
+        match buffer.iter().copied().position(|ch| ch == Unit::NUL) {
             Some(nul)   => Ok(&buffer[..=nul]),
             None        => Err(NotNulTerminatedError(())),
         }
@@ -101,12 +110,22 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     /// `O(n)` to locate the terminal `\0`.
     pub fn validate(&self) -> Result<(), NotNulTerminatedError> { self.to_units_with_nul().map(|_| ()) }
 
+    /// Borrow the buffer as a [`CStrNonNull`], returning <code>[Err]\([NotNulTerminatedError]\)</code> instead if the buffer isn't `\0`-terminated.
+    /// Once validated, the result can be passed to anything accepting [`AsCStr`] or [`AsOptCStr`].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn as_cstr_non_null(&self) -> Result<CStrNonNull<'_, U>, NotNulTerminatedError> {
+        self.to_units_with_nul().map(|units| unsafe { CStrNonNull::from_units_with_nul_unchecked(units) })
+    }
+
     /// Access the underlying byte buffer of `self`.
     ///
     /// ### Safety
     ///
     /// Many C APIs assume the underlying buffer is `\0`-terminated, and this method would let you change that.
     /// However, it's worth noting that [`CStrBuf`] technically makes no such guarantee!
+    ///
+    /// Whatever you write must also remain a valid string of `U`s (e.g. don't split a [`Utf8`] character.)
     pub unsafe fn buffer_mut(&mut self) -> &mut [U] { &mut self.buffer[..] }
 
     /// Ensure the buffer is `\0`-terminated by setting the last character to be `\0`.
@@ -114,9 +133,9 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     /// ### Panics
     ///
     /// If `self.buffer.is_empty()` (...did you create a `CStrBuf<[u8; 0]>` or something?  Weirdo.)
-    pub fn nul_truncate(&mut self) -> CStrNonNull<U> {
+    pub fn nul_truncate(&mut self) -> CStrNonNull<'_, U> {
         let buffer = &mut self.buffer[..];
-        *buffer.last_mut().unwrap() = private::Unit::NUL;
+        *buffer.last_mut().unwrap() = Unit::NUL;
         unsafe { CStrNonNull::from_ptr_unchecked_unbounded(buffer.as_ptr().cast()) }
     }
 
@@ -126,55 +145,112 @@ impl<U: Unit, const N: usize> CStrBuf<U, N> {
     /// ### Panics
     ///
     /// If `self.buffer.is_empty()` (...did you create a `CStrBuf<[u8; 0]>` or something?  Weirdo.)
-    pub fn set_truncate(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> {
+    pub fn set_truncate(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> where U : AnyUnitSequence {
         let src = data.as_ref();
         let dst = &mut self.buffer[..];
         let n = (dst.len()-1).min(src.len());
         dst[..n].copy_from_slice(&src[..n]);
-        dst[n] = private::Unit::NUL;
-        if src.len() >= dst.len() { Err(BufferTooSmallError(()))? }
+        dst[n] = Unit::NUL;
+        if src.len() >= dst.len() { Err(BufferTooSmallError::new(src.len() + 1, dst.len()))? }
         Ok(())
     }
 
     /// Modifies the buffer to contain `data` + `\0`.
     /// If `data` will not fit, it will be truncated - *without* a final `\0` - before returning <code>[Err]\([BufferTooSmallError]\)</code>.
-    pub unsafe fn set_truncate_without_nul(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> {
+    ///
+    /// ### Safety
+    ///
+    /// The buffer might not be `\0`-terminated afterwards.  It's up to you to ensure nothing relies on it being so.
+    pub unsafe fn set_truncate_without_nul(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> where U : AnyUnitSequence {
         let src = data.as_ref();
         let dst = &mut self.buffer[..];
         let n = dst.len().min(src.len());
         dst[..n].copy_from_slice(&src[..n]);
-        if let Some(dst) = dst.get_mut(n) { *dst = private::Unit::NUL; }
-        if src.len() > dst.len() { Err(BufferTooSmallError(()))? }
+        if let Some(dst) = dst.get_mut(n) { *dst = Unit::NUL; }
+        if src.len() > dst.len() { Err(BufferTooSmallError::new(src.len(), dst.len()))? }
         Ok(())
     }
 
     /// Modifies the buffer to contain `data` + `\0`.
     /// If `data` + '\0' will not fit, <code>[Err]\([BufferTooSmallError]\)</code> will be returned without modifying the underlying buffer.
-    pub fn try_set(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> {
+    pub fn try_set(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> where U : AnyUnitSequence {
         let src = data.as_ref();
         let dst = &mut self.buffer[..];
-        if src.len() >= dst.len() { Err(BufferTooSmallError(()))? }
+        if src.len() >= dst.len() { Err(BufferTooSmallError::new(src.len() + 1, dst.len()))? }
         dst[..src.len()].copy_from_slice(src);
-        dst[src.len()] = private::Unit::NUL;
+        dst[src.len()] = Unit::NUL;
         Ok(())
     }
 
     /// Modifies the buffer to contain `data` (and a `\0` - but only if it will fit!)
     /// If `data` will not fit, <code>[Err]\([BufferTooSmallError]\)</code> will be returned without modifying the underlying buffer.
-    pub unsafe fn try_set_without_nul(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> {
+    ///
+    /// ### Safety
+    ///
+    /// The buffer might not be `\0`-terminated afterwards.  It's up to you to ensure nothing relies on it being so.
+    pub unsafe fn try_set_without_nul(&mut self, data: &(impl AsRef<[U]> + ?Sized)) -> Result<(), BufferTooSmallError> where U : AnyUnitSequence {
         let src = data.as_ref();
         let dst = &mut self.buffer[..];
-        if src.len() > dst.len() { Err(BufferTooSmallError(()))? }
+        if src.len() > dst.len() { Err(BufferTooSmallError::new(src.len(), dst.len()))? }
         dst[..src.len()].copy_from_slice(src);
-        if let Some(dst) = dst.get_mut(src.len()) { *dst = private::Unit::NUL; }
+        if let Some(dst) = dst.get_mut(src.len()) { *dst = Unit::NUL; }
+        Ok(())
+    }
+
+    /// Modifies the buffer to contain `data` (encoded as UTF8, UTF16, or UTF32 depending on `U`) + `\0`.
+    /// If `data` contains a `\0` (or a character `U` can't encode, per [`Unit::check_char`]), or `data` + `\0` will not fit, <code>[Err]\([SetStrError]\)</code> will be returned without modifying the underlying buffer.
+    pub fn try_set_str(&mut self, data: &str) -> Result<(), SetStrError> {
+        let required = check_str::<U>(data).map_err(Into::into)? + 1;
+        if required > N { Err(BufferTooSmallError::new(required, N))? }
+        self.set_str_truncate(data)
+    }
+
+    /// Modifies the buffer to contain `data` (encoded as UTF8, UTF16, or UTF32 depending on `U`) + `\0`.
+    /// If `data` will not fit, it will be truncated - on a character boundary - with a final `\0` before returning <code>[Err]\([SetStrError::BufferTooSmall]\)</code>.
+    /// If `data` contains a `\0`, <code>[Err]\([SetStrError::InteriorNul]\)</code> will be returned without modifying the underlying buffer.
+    ///
+    /// ### Panics
+    ///
+    /// If `self.buffer.is_empty()`
+    pub fn set_str_truncate(&mut self, data: &str) -> Result<(), SetStrError> {
+        check_str_nul::<U>(data)?;
+        let dst = &mut self.buffer[..];
+        let max = dst.len() - 1;
+        let mut buf = Default::default();
+        let mut n = 0;
+        let mut required = 0;
+        for ch in data.chars() {
+            let units = U::encode_char(ch, &mut buf);
+            if n == required && n + units.len() <= max {
+                dst[n..n+units.len()].copy_from_slice(units);
+                n += units.len();
+            }
+            required += units.len();
+        }
+        dst[n] = Unit::NUL;
+        if required > max { Err(BufferTooSmallError::new(required + 1, dst.len()))? }
         Ok(())
     }
 
     /// Convert the buffer to a <code>&[str]</code>, allocating and replacing invalid UTF8 with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER] if necessary.
     ///
     /// `O(n)` to locate the terminal `\0`.
-    #[cfg(feature = "std")]
-    pub fn to_string_lossy(&self) -> Cow<'_, str> { private::Unit::to_string_lossy(self.to_units()) }
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> { Unit::to_string_lossy(self.to_units()) }
+
+    /// Append the buffer to `out`, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.  Unlike [`to_string_lossy`](Self::to_string_lossy), this reuses `out`'s allocation.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy_into(&self, out: &mut String) { out.extend(decode_lossy(self.to_units())) }
+
+    /// Get a [`Display`](core::fmt::Display)able adapter for the buffer, which decodes without allocating.
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn display(&self) -> CStrDisplay<'_, U> { CStrDisplay::new(self.to_units()) }
+
+    /// Get a [`Debug`]able adapter which shows the string portion of the buffer, followed by a hex dump of the *entire* buffer (including anything after the first `\0`.)
+    pub fn debug_buffer(&self) -> CStrBufDebug<'_, U> { CStrBufDebug::new(self.buffer()) }
 }
 
 impl<const N: usize> CStrBuf<u8, N> {
@@ -185,7 +261,6 @@ impl<const N: usize> CStrBuf<u8, N> {
     /// You might prefer [`to_string_lossy`](Self::to_string_lossy), which cannot fail, or [`to_str`](Self::to_str), which can fail due to invalid UTF8, but not due to missing `\0`s.
     ///
     /// `O(n)` to locate the terminal `\0`.
-    #[cfg(feature = "std")]
     pub fn to_cstr(&self) -> Result<&CStr, NotNulTerminatedError> { self.to_bytes_with_nul().map(|bytes| unsafe { CStr::from_bytes_with_nul_unchecked(bytes) }) }
 
     /// Attempt to convert the buffer to a <code>&[str]</code>, returning <code>[Err]\([Utf8Error]\)</code> instead if the underlying buffer wasn't valid UTF8.
@@ -221,11 +296,37 @@ impl<const N: usize> CStrBuf<u8, N> {
 }
 
 impl<U: Unit, const N: usize> Default for CStrBuf<U, N> {
-    fn default() -> Self { Self { buffer: U::zeroed() } }
+    fn default() -> Self { Self { buffer: [U::NUL; N] } }
 }
 
 impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result { private::Unit::debug(self.to_units(), f) }
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { Unit::debug(self.to_units(), f) }
+}
+
+/// The number of `U`s `data` encodes to (excluding any terminal `\0`.)
+fn encoded_len<U: Unit>(data: &str) -> usize {
+    let mut buf = Default::default();
+    data.chars().map(|ch| U::encode_char(ch, &mut buf).len()).sum()
+}
+
+/// Reject `data` if it contains a `\0` or a character `U` can't encode exactly, otherwise return the number of `U`s it encodes to (excluding any terminal `\0`.)
+fn check_str<U: Unit>(data: &str) -> Result<usize, U::StrError> {
+    let mut buf = Default::default();
+    let mut len = 0;
+    for ch in data.chars() {
+        if ch == '\0' { Err(InteriorNulError::new(len))? }
+        U::check_char(ch, len)?;
+        len += U::encode_char(ch, &mut buf).len();
+    }
+    Ok(len)
+}
+
+/// Reject `data` if it contains a `\0`, reporting the position of the first one in `U`s.
+fn check_str_nul<U: Unit>(data: &str) -> Result<(), InteriorNulError> {
+    match data.find('\0') {
+        None        => Ok(()),
+        Some(nul)   => Err(InteriorNulError::new(encoded_len::<U>(&data[..nul]))),
+    }
 }
 
 
@@ -277,6 +378,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
 
 
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn from8() {
     type CB8 = CStrBuf<u8, 8>;
     {
@@ -297,6 +399,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     }
 }
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn from16() {
     type CB8 = CStrBuf<u16, 8>;
     let u12345678910 = [1u16, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -320,6 +423,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
 
 
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn set8() {
     type CB8 = CStrBuf<u8, 8>;
     let reference = CB8::from_truncate(b"ref");
@@ -339,16 +443,16 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     }
     {
         let mut cb = reference;
-        assert_eq!(cb.try_set(b"1234567890").is_err(), true);
+        assert_eq!(cb.try_set(b"1234567890"), Err(BufferTooSmallError::new(11, 8)));
         assert_eq!(cb.to_bytes(), b"ref");
-        assert_eq!(cb.try_set(b"12345678").is_err(), true);
+        assert_eq!(cb.try_set(b"12345678"), Err(BufferTooSmallError::new(9, 8)));
         assert_eq!(cb.to_bytes(), b"ref");
         assert_eq!(cb.try_set(b"1234").is_err(), false);
         assert_eq!(cb.to_bytes(), b"1234");
     }
     unsafe {
         let mut cb = reference;
-        assert_eq!(cb.try_set_without_nul(b"1234567890").is_err(), true);
+        assert_eq!(cb.try_set_without_nul(b"1234567890"), Err(BufferTooSmallError::new(10, 8)));
         assert_eq!(cb.to_bytes(), b"ref");
         assert_eq!(cb.try_set_without_nul(b"12345678").is_err(), false);
         assert_eq!(cb.to_bytes(), b"12345678");
@@ -357,6 +461,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     }
 }
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn set16() {
     type CB8 = CStrBuf<u16, 8>;
     let u12345678910 = [1u16, 2, 3, 4, 5, 6, 7, 8, 9, 10];
@@ -398,7 +503,114 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
 
 
 
+#[test] fn set_str() {
+    let reference = CStrBuf::<u8, 8>::from_truncate(b"ref");
+    {
+        let mut cb = reference;
+        assert_eq!(cb.try_set_str("1234567"), Ok(()));
+        assert_eq!(cb.to_bytes(), b"1234567");
+        assert_eq!(cb.try_set_str("12345678"), Err(BufferTooSmallError::new(9, 8).into()));
+        assert_eq!(cb.to_bytes(), b"1234567");
+        assert_eq!(cb.try_set_str("日本"), Ok(()));
+        assert_eq!(cb.to_str(), Ok("日本"));
+        assert_eq!(cb.try_set_str("日本語"), Err(BufferTooSmallError::new(10, 8).into()));
+        assert_eq!(cb.to_str(), Ok("日本"));
+    }
+    {
+        let mut cb = reference;
+        assert_eq!(cb.set_str_truncate("1234"), Ok(()));
+        assert_eq!(cb.to_bytes(), b"1234");
+        assert_eq!(cb.set_str_truncate("日本語"), Err(BufferTooSmallError::new(10, 8).into()));
+        assert_eq!(cb.to_str(), Ok("日本")); // not split mid-character
+        assert_eq!(cb.set_str_truncate("123456日1"), Err(BufferTooSmallError::new(11, 8).into()));
+        assert_eq!(cb.to_str(), Ok("123456")); // nothing after the first character that didn't fit
+    }
+    {
+        let mut cb = CStrBuf::<u16, 4>::default();
+        assert_eq!(cb.try_set_str("a\u{1F600}"), Ok(()));
+        assert_eq!(cb.to_units(), &[b'a' as u16, 0xD83D, 0xDE00]);
+        assert_eq!(cb.set_str_truncate("ab\u{1F600}"), Err(BufferTooSmallError::new(5, 4).into()));
+        assert_eq!(cb.to_units(), &[b'a' as u16, b'b' as u16]);
+
+        let mut cb = CStrBuf::<u32, 4>::default();
+        assert_eq!(cb.try_set_str("ab\u{1F600}"), Ok(()));
+        assert_eq!(cb.to_units(), &[b'a' as u32, b'b' as u32, 0x1F600]);
+    }
+    {
+        let mut cb = CStrBuf::<u16, 8>::from_truncate(&[0x61, 0x62]);
+        assert_eq!(cb.try_set_str("\u{1F600}\0c"), Err(InteriorNulError::new(2).into()));
+        assert_eq!(cb.set_str_truncate("\u{1F600}\0c"), Err(InteriorNulError::new(2).into()));
+        assert_eq!(cb.to_units(), &[0x61, 0x62]); // untouched
+        assert_eq!(CStrBuf::<u8, 2>::default().try_set_str("\u{E9}\0"), Err(InteriorNulError::new(2).into())); // reported before size
+    }
+}
+
+
+
+#[cfg(feature = "alloc")]
+#[test] fn as_cstr_non_null() {
+    let mut buf = CStrBuf::<u8, 4>::from_truncate(b"abc");
+    assert_eq!(buf.as_cstr_non_null().map(|s| s.to_units()), Ok(&b"abc"[..]));
+    assert_eq!(with_cstr(&buf, |s: CStrNonNull| s.to_units().len()), Ok(3));
+    assert_eq!(with_opt_cstr(&buf, |s: Option<CStrNonNull>| s.is_some()), Ok(true));
+
+    unsafe { buf.buffer_mut() }.copy_from_slice(b"abcd");
+    assert_eq!(buf.as_cstr_non_null().map(|s| s.to_units()), Err(NotNulTerminatedError(())));
+    assert_eq!(with_cstr(&buf, |_: CStrNonNull| ()), Err(NotNulTerminatedError(())));
+
+    let wide = CStrBuf::<u16, 4>::from_truncate(&[1, 2]);
+    assert_eq!(with_cstr(&wide, |s: CStrNonNull<u16>| s.to_units().to_vec()), Ok([1, 2].to_vec()));
+}
+
+#[cfg(feature = "std")]
+#[test] fn display() {
+    for bytes in [&b"h\xC3\xA9llo \xF0\x9F\x98\x80"[..], b"a\xE2\x82", b"\xE2\x82a", b"\xF0\x9F\x98", b"\xFF\xC0\x80\xED\xA0\x80"] {
+        let cb = CStrBuf::<u8, 16>::from_truncate(bytes);
+        let mut out = String::new();
+        cb.to_string_lossy_into(&mut out);
+        assert_eq!(out, String::from_utf8_lossy(bytes));
+        assert_eq!(format!("{}", cb.display()), String::from_utf8_lossy(bytes));
+    }
+
+    let units = [b'h' as u16, 0xD83D, 0xDE00, 0xDC00, b'!' as u16];
+    let cb = CStrBuf::<u16, 8>::from_truncate(&units);
+    assert_eq!(format!("{}", cb.display()), "h\u{1F600}\u{FFFD}!");
+
+    let cb = CStrBuf::<u32, 8>::from_truncate(&[b'h' as u32, 0x1F600, 0xD800, 0x110000]);
+    assert_eq!(format!("{}", cb.display()), "h\u{1F600}\u{FFFD}\u{FFFD}");
+
+    let cb = CStrBuf::<u8, 8>::from_truncate(b"h\xC3\xA9llo");
+    let s = "h\u{E9}llo";
+    assert_eq!(format!("{:8}|",     cb.display()), format!("{:8}|",     s));
+    assert_eq!(format!("{:>8}|",    cb.display()), format!("{:>8}|",    s));
+    assert_eq!(format!("{:^8}|",    cb.display()), format!("{:^8}|",    s));
+    assert_eq!(format!("{:*<7.3}|", cb.display()), format!("{:*<7.3}|", s));
+    assert_eq!(format!("{:.0}|",    cb.display()), format!("{:.0}|",    s));
+    assert_eq!(format!("{:2}|",     cb.display()), format!("{:2}|",     s));
+    assert_eq!(format!("{:^8}|",    cb.display()), " h\u{E9}llo  |");
+}
+
+
+
+#[cfg(feature = "std")]
+#[test] fn debug_buffer() {
+    let mut cb = CStrBuf::<u8, 8>::from_truncate(b"abc\0de");
+    assert_eq!(format!("{:?}", cb.debug_buffer()), "\"abc\" [61 62 63 00 64 65 00 00]");
+    unsafe { cb.buffer_mut().copy_from_slice(b"\xFFfffffff") };
+    assert_eq!(format!("{:?}", cb.debug_buffer()), "\"\\xfffffffff\" [ff 66 66 66 66 66 66 66]");
+
+    let cb = CStrBuf::<u16, 4>::from_truncate(&[0x65E5, 0xD800]);
+    assert_eq!(format!("{:?}", cb), "\"日\\ud800\"");
+    assert_eq!(format!("{:?}", cb.debug_buffer()), "\"日\\ud800\" [65e5 d800 0000 0000]");
+
+    let cb = CStrBuf::<u32, 2>::from_truncate(&[0x1F600]);
+    assert_eq!(format!("{:?}", cb.debug_buffer()), "\"\u{1F600}\" [0001f600 00000000]");
+}
+
+
+
 #[allow(overflowing_literals)]
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn struct_interop_narrow() {
     use core::mem::*;
 
@@ -463,7 +675,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     assert_eq!(r.full           .to_bytes_with_nul(), Err(NotNulTerminatedError(())));
     assert_eq!(r.not_unicode    .to_bytes_with_nul(), Ok(&b"\xFF\xFF\0"[..]));
 
-    #[cfg(feature = "std")] {
+    {
         assert_eq!(r.empty          .to_cstr(), Ok(CStr::from_bytes_with_nul(b"\0").unwrap()));
         assert_eq!(r.empty2         .to_cstr(), Ok(CStr::from_bytes_with_nul(b"\0").unwrap()));
         assert_eq!(r.empty3         .to_cstr(), Ok(CStr::from_bytes_with_nul(b"\0").unwrap()));
@@ -479,7 +691,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     assert_eq!(r.full           .to_str(), Ok("ffffffffffffffff"));
     assert_eq!(r.not_unicode    .to_str().is_err(), true);
 
-    #[cfg(feature = "std")] {
+    #[cfg(feature = "alloc")] {
         assert_eq!(r.empty          .to_string_lossy(), "");
         assert_eq!(r.empty2         .to_string_lossy(), "");
         assert_eq!(r.empty3         .to_string_lossy(), "");
@@ -537,6 +749,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
     assert_eq!(r.not_unicode    .validate().is_err(), false);
 }
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn struct_interop_wide() {
     use core::mem::*;
 
@@ -619,7 +832,7 @@ impl<U: Unit, const N: usize> Debug for CStrBuf<U, N> {
         assert_eq!(r.not_unicode    .to_u16str(), U16Str::from_slice(&[0xDC00, 0xDC00]));
     }
 
-    #[cfg(feature = "std")] {
+    #[cfg(feature = "alloc")] {
         assert_eq!(r.empty          .to_string_lossy(), "");
         assert_eq!(r.empty2         .to_string_lossy(), "");
         assert_eq!(r.empty3         .to_string_lossy(), "");
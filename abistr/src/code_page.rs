@@ -0,0 +1,308 @@
+use crate::*;
+
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+#[cfg(feature = "alloc")] use alloc::string::String;
+
+
+
+/// How to interpret the bytes of a [`u8`] C string: UTF8, or a legacy ANSI code page.
+///
+/// Many C libraries return text in the system code page, which [`CStrPtr::to_string_lossy`] would mangle as UTF8.
+/// [`Latin1`](Self::Latin1), [`Ascii`](Self::Ascii), and [`Windows1252`](Self::Windows1252) are built in.
+/// Other code pages (Windows-1250 ..= 1258, Shift_JIS, GBK, EUC-KR, Big5, ...) are available via the `encoding_rs` feature.
+/// [`Mutf8`](Self::Mutf8) and [`Cesu8`](Self::Cesu8) cover JNI / Java and Oracle style "UTF8".
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// let mut buf = CStrBuf::<u8, 8>::default();
+/// buf.set_with(CodePage::Windows1252, "\u{20AC}5 caf\u{E9}").unwrap();
+/// assert_eq!(buf.to_units(), b"\x805 caf\xE9");
+/// # #[cfg(feature = "alloc")] {
+/// assert_eq!(buf.to_string_with(CodePage::Windows1252), "\u{20AC}5 caf\u{E9}");
+/// assert_eq!(buf.to_string_with(CodePage::Latin1),      "\u{80}5 caf\u{E9}");
+/// assert_eq!(buf.to_string_lossy(),                     "\u{FFFD}5 caf\u{FFFD}");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CodePage {
+    /// UTF8 (invalid sequences decode as [`U+FFFD`](core::char::REPLACEMENT_CHARACTER).)
+    Utf8,
+    /// Strict 7-bit ASCII: bytes `0x80 ..= 0xFF` decode as [`U+FFFD`](core::char::REPLACEMENT_CHARACTER), and only `'\0' ..= '\x7F'` can be encoded.
+    Ascii,
+    /// ISO-8859-1: every byte decodes to the [`char`] of the same value, and only `'\0' ..= '\u{FF}'` can be encoded.
+    Latin1,
+    /// Windows-1252 ("ANSI" on western Windows installs): Latin-1, with `0x80 ..= 0x9F` mostly replaced by punctuation (`€`, `“`, `”`, ...)
+    ///
+    /// The 5 unassigned bytes (`0x81`, `0x8D`, `0x8F`, `0x90`, `0x9D`) round trip as their C1 control characters, as with `MultiByteToWideChar`.
+    Windows1252,
+    /// Java's "Modified UTF-8" (as used by JNI's `GetStringUTFChars` etc.): [`Cesu8`](Self::Cesu8), but with `'\0'` encoded as `C0 80`, so it never terminates the string early.
+    ///
+    /// See also [`cstr_mutf8!`] for literals.
+    Mutf8,
+    /// CESU-8: UTF8, but with supplementary characters (`U+10000 ..`) encoded as a UTF16 surrogate pair, 3 bytes per surrogate.
+    Cesu8,
+    /// Any [`encoding_rs::Encoding`], e.g. [`encoding_rs::SHIFT_JIS`] or [`encoding_rs::GBK`].
+    #[cfg(feature = "encoding_rs")]
+    EncodingRs(&'static encoding_rs::Encoding),
+}
+
+#[cfg(feature = "encoding_rs")] impl From<&'static encoding_rs::Encoding> for CodePage {
+    fn from(encoding: &'static encoding_rs::Encoding) -> Self { CodePage::EncodingRs(encoding) }
+}
+
+impl CodePage {
+    /// Decode `bytes`, replacing anything invalid (or unassigned) with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    /// Borrows `bytes` if possible (e.g. if they're all ASCII.)
+    #[cfg(feature = "alloc")]
+    pub fn decode_lossy(self, bytes: &[u8]) -> Cow<'_, str> {
+        let single_byte : fn(u8) -> char = match self {
+            CodePage::Utf8                      => return String::from_utf8_lossy(bytes),
+            CodePage::Mutf8 | CodePage::Cesu8   => return cesu8_decode_lossy(bytes, self == CodePage::Mutf8),
+            #[cfg(feature = "encoding_rs")]
+            CodePage::EncodingRs(encoding)      => return encoding.decode_without_bom_handling(bytes).0,
+            CodePage::Ascii                     => |b| if b < 0x80 { char::from(b) } else { core::char::REPLACEMENT_CHARACTER },
+            CodePage::Latin1                    => char::from,
+            CodePage::Windows1252               => windows_1252_decode,
+        };
+        if bytes.is_ascii() { return Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) }) }
+        Cow::Owned(bytes.iter().copied().map(single_byte).collect())
+    }
+
+    /// Encode `s` into `dst` (without a terminal `\0`), returning the number of bytes written.
+    /// Doesn't write anything if `s` contains unmappable characters.
+    fn encode_into(self, s: &str, dst: &mut [u8]) -> Result<usize, EncodeError> {
+        let single_byte : fn(char) -> Option<u8> = match self {
+            CodePage::Utf8                      => return utf8_encode_into(s, dst),
+            CodePage::Mutf8 | CodePage::Cesu8   => return cesu8_encode_into(s, dst, self == CodePage::Mutf8),
+            #[cfg(feature = "encoding_rs")]
+            CodePage::EncodingRs(encoding)      => return encoding_rs_encode_into(encoding, s, dst),
+            CodePage::Ascii                     => |ch| if ch.is_ascii() { Some(ch as u8) } else { None },
+            CodePage::Latin1                    => |ch| if u32::from(ch) <= 0xFF { Some(ch as u8) } else { None },
+            CodePage::Windows1252               => windows_1252_encode,
+        };
+        for (position, ch) in s.char_indices() {
+            if single_byte(ch).is_none() { Err(UnmappableCharError::new(ch, position))? }
+        }
+        let required = s.chars().count();
+        if required > dst.len() { Err(BufferTooSmallError::new(required + 1, dst.len() + 1))? }
+        for (dst, ch) in dst.iter_mut().zip(s.chars()) { *dst = single_byte(ch).unwrap_or(b'?'); }
+        Ok(required)
+    }
+}
+
+const WINDOWS_1252_80_9F : [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))] // only used by decode_lossy
+fn windows_1252_decode(b: u8) -> char {
+    match b {
+        0x80 ..= 0x9F   => WINDOWS_1252_80_9F[usize::from(b - 0x80)],
+        _               => char::from(b),
+    }
+}
+
+fn windows_1252_encode(ch: char) -> Option<u8> {
+    match u32::from(ch) {
+        0x00 ..= 0x7F | 0xA0 ..= 0xFF   => Some(ch as u8),
+        _                               => WINDOWS_1252_80_9F.iter().position(|c| *c == ch).map(|i| 0x80 + i as u8),
+    }
+}
+
+fn utf8_encode_into(s: &str, dst: &mut [u8]) -> Result<usize, EncodeError> {
+    if s.len() > dst.len() { Err(BufferTooSmallError::new(s.len() + 1, dst.len() + 1))? }
+    dst[..s.len()].copy_from_slice(s.as_bytes());
+    Ok(s.len())
+}
+
+/// Decode CESU-8 (or MUTF-8, which additionally decodes `C0 80` as `'\0'`.)
+#[cfg(feature = "alloc")]
+fn cesu8_decode_lossy(bytes: &[u8], mutf8: bool) -> Cow<'_, str> {
+    if bytes.is_ascii() { return Cow::Borrowed(unsafe { core::str::from_utf8_unchecked(bytes) }) }
+    let mut out = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some(&lead) = rest.first() {
+        let (ch, n) = match lead {
+            0x00 ..= 0x7F                               => (Some(char::from(lead)), 1),
+            0xC0 if mutf8 && rest.get(1) == Some(&0x80) => (Some('\0'), 2),
+            _ => match cesu8_decode_unit(rest) {
+                None                                    => (None, 1),
+                Some((hi @ 0xD800 ..= 0xDBFF, n))       => match cesu8_decode_unit(&rest[n..]) {
+                    Some((lo @ 0xDC00 ..= 0xDFFF, m))   => (core::char::from_u32(0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)), n + m),
+                    _                                   => (None, n),
+                },
+                Some((unit, n))                         => (core::char::from_u32(unit), n), // `None` for unpaired low surrogates
+            },
+        };
+        out.push(ch.unwrap_or(core::char::REPLACEMENT_CHARACTER));
+        rest = &rest[n..];
+    }
+    Cow::Owned(out)
+}
+
+/// Decode a 2 or 3 byte sequence (which might be a surrogate), rejecting overlong encodings.
+#[cfg(feature = "alloc")]
+fn cesu8_decode_unit(bytes: &[u8]) -> Option<(u32, usize)> {
+    let cont = |i: usize| bytes.get(i).copied().filter(|b| b & 0xC0 == 0x80).map(|b| u32::from(b & 0x3F));
+    match bytes[0] {
+        b @ 0xC2 ..= 0xDF   => Some(((u32::from(b & 0x1F) << 6) | cont(1)?, 2)),
+        b @ 0xE0 ..= 0xEF   => Some(((u32::from(b & 0x0F) << 12) | (cont(1)? << 6) | cont(2)?, 3)).filter(|(unit, _)| *unit >= 0x800),
+        _                   => None,
+    }
+}
+
+/// Encode `ch` as CESU-8 (or MUTF-8, which additionally encodes `'\0'` as `C0 80`.)
+fn cesu8_encode_char(ch: char, mutf8: bool, buf: &mut [u8; 6]) -> &[u8] {
+    if ch == '\0' && mutf8 {
+        buf[..2].copy_from_slice(&[0xC0, 0x80]);
+        return &buf[..2];
+    }
+    if u32::from(ch) <= 0xFFFF { return ch.encode_utf8(buf).as_bytes() }
+    let mut surrogates = [0u16; 2];
+    for (dst, unit) in buf.chunks_exact_mut(3).zip(ch.encode_utf16(&mut surrogates).iter()) {
+        dst.copy_from_slice(&[0xE0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]);
+    }
+    &buf[..]
+}
+
+fn cesu8_encode_into(s: &str, dst: &mut [u8], mutf8: bool) -> Result<usize, EncodeError> {
+    let mut buf = [0u8; 6];
+    let required = s.chars().map(|ch| cesu8_encode_char(ch, mutf8, &mut buf).len()).sum::<usize>();
+    if required > dst.len() { Err(BufferTooSmallError::new(required + 1, dst.len() + 1))? }
+    let mut n = 0;
+    for ch in s.chars() {
+        let encoded = cesu8_encode_char(ch, mutf8, &mut buf);
+        dst[n .. n + encoded.len()].copy_from_slice(encoded);
+        n += encoded.len();
+    }
+    Ok(n)
+}
+
+#[cfg(feature = "encoding_rs")]
+fn encoding_rs_encode_into(encoding: &'static encoding_rs::Encoding, s: &str, dst: &mut [u8]) -> Result<usize, EncodeError> {
+    use encoding_rs::EncoderResult;
+    let mut encoder = encoding.new_encoder();
+    let mut scratch = [0u8; 16]; // once `dst` is full, keep encoding into `scratch` to find the required length
+    let (mut read, mut written, mut required, mut full) = (0, 0, 0, false);
+    loop {
+        let out = if full { &mut scratch[..] } else { &mut dst[written..] };
+        let (result, r, w) = encoder.encode_from_utf8_without_replacement(&s[read..], out, true);
+        read += r;
+        required += w;
+        if !full { written += w; }
+        match result {
+            EncoderResult::InputEmpty       => break,
+            EncoderResult::OutputFull       => full = true,
+            EncoderResult::Unmappable(ch)   => Err(UnmappableCharError::new(ch, read - ch.len_utf8()))?,
+        }
+    }
+    if full { Err(BufferTooSmallError::new(required + 1, dst.len() + 1))? }
+    Ok(written)
+}
+
+
+
+impl<'s> CStrPtr<'s, u8> {
+    /// Decode the string from `encoding`, replacing anything invalid with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with(&self, encoding: CodePage) -> Cow<'s, str> { encoding.decode_lossy(self.to_units()) }
+}
+
+impl<'s> CStrNonNull<'s, u8> {
+    /// Decode the string from `encoding`, replacing anything invalid with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with(&self, encoding: CodePage) -> Cow<'s, str> { encoding.decode_lossy(self.to_units()) }
+}
+
+impl<const N: usize> CStrBuf<u8, N> {
+    /// Decode the buffer from `encoding`, replacing anything invalid with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_with(&self, encoding: CodePage) -> Cow<'_, str> { encoding.decode_lossy(self.to_units()) }
+
+    /// Modifies the buffer to contain `data` (encoded per `encoding`) + `\0`.
+    /// If `data` contains characters `encoding` can't represent, contains a `\0` (unless `encoding` is [`CodePage::Mutf8`]), or won't fit,
+    /// <code>[Err]\([EncodeError]\)</code> will be returned without modifying the underlying buffer.
+    pub fn set_with(&mut self, encoding: CodePage, data: &str) -> Result<(), EncodeError> {
+        if encoding != CodePage::Mutf8 { if let Some(nul) = data.find('\0') { Err(InteriorNulError::new(nul))? } }
+        if N == 0 { Err(BufferTooSmallError::new(1, 0))? }
+        let mut buffer = [0u8; N];
+        let n = encoding.encode_into(data, &mut buffer[..N-1])?;
+        buffer[n] = 0;
+        unsafe { self.try_set_without_nul(&buffer[..=n]) }.map_err(EncodeError::from)
+    }
+}
+
+
+
+#[test] fn code_pages() {
+    let mut buf = CStrBuf::<u8, 6>::default();
+    buf.set_with(CodePage::Latin1, "caf\u{E9}").unwrap();
+    assert_eq!(buf.to_units(), b"caf\xE9");
+    assert_eq!(buf.set_with(CodePage::Latin1, "\u{20AC}"), Err(EncodeError::Unmappable(UnmappableCharError::new('\u{20AC}', 0))));
+    assert_eq!(buf.set_with(CodePage::Ascii, "ab\u{E9}"), Err(EncodeError::Unmappable(UnmappableCharError::new('\u{E9}', 2))));
+    assert_eq!(buf.set_with(CodePage::Windows1252, "\u{E9}\u{20AC}\u{201C}\u{81}\u{178}"), Ok(()));
+    assert_eq!(buf.to_units(), b"\xE9\x80\x93\x81\x9F");
+    assert_eq!(buf.set_with(CodePage::Windows1252, "\u{80}"), Err(EncodeError::Unmappable(UnmappableCharError::new('\u{80}', 0))));
+    assert_eq!(buf.set_with(CodePage::Latin1, "abcdef"), Err(EncodeError::BufferTooSmall(BufferTooSmallError::new(7, 6))));
+    assert_eq!(buf.set_with(CodePage::Utf8, "\u{E9}\u{E9}\u{E9}"), Err(EncodeError::BufferTooSmall(BufferTooSmallError::new(7, 6))));
+    assert_eq!(buf.to_units(), b"\xE9\x80\x93\x81\x9F", "failures shouldn't modify the buffer");
+    buf.set_with(CodePage::Utf8, "\u{E9}\u{E9}").unwrap();
+    assert_eq!(buf.to_str(), Ok("\u{E9}\u{E9}"));
+    assert_eq!(CStrBuf::<u8, 0>::default().set_with(CodePage::Utf8, ""), Err(EncodeError::BufferTooSmall(BufferTooSmallError::new(1, 0))));
+    for &cp in [CodePage::Utf8, CodePage::Cesu8, CodePage::Ascii, CodePage::Latin1, CodePage::Windows1252].iter() {
+        assert_eq!(buf.set_with(cp, "ab\0c"), Err(EncodeError::InteriorNul(InteriorNulError::new(2))), "{:?}", cp);
+        assert_eq!(buf.to_str(), Ok("\u{E9}\u{E9}"), "failures shouldn't modify the buffer");
+    }
+
+    for b in 0 ..= 0xFF { assert_eq!(windows_1252_encode(windows_1252_decode(b)), Some(b)); }
+
+    #[cfg(feature = "alloc")] {
+        let s = cstr!(b"\x93caf\xE9\x94");
+        assert_eq!(s.to_string_with(CodePage::Windows1252), "\u{201C}caf\u{E9}\u{201D}");
+        assert_eq!(s.to_string_with(CodePage::Latin1), "\u{93}caf\u{E9}\u{94}");
+        assert_eq!(s.to_string_with(CodePage::Ascii), "\u{FFFD}caf\u{FFFD}\u{FFFD}");
+        assert_eq!(s.to_string_with(CodePage::Utf8), "\u{FFFD}caf\u{FFFD}");
+        assert!(matches!(CStrPtr::from_units_with_nul(b"abc\0").unwrap().to_string_with(CodePage::Windows1252), Cow::Borrowed("abc")));
+    }
+
+    let mut buf = CStrBuf::<u8, 16>::default();
+    buf.set_with(CodePage::Mutf8, "a\0\u{E9}\u{1F980}").unwrap();
+    assert_eq!(buf.to_units(), b"a\xC0\x80\xC3\xA9\xED\xA0\xBE\xED\xB6\x80");
+    buf.set_with(CodePage::Cesu8, "\u{10000}").unwrap();
+    assert_eq!(buf.to_units(), b"\xED\xA0\x80\xED\xB0\x80");
+    assert_eq!(buf.set_with(CodePage::Mutf8, "\u{1F980}\u{1F980}\u{1F980}"), Err(EncodeError::BufferTooSmall(BufferTooSmallError::new(19, 16))));
+
+    #[cfg(feature = "alloc")] {
+        let jni = cstr!(b"a\xC0\x80\xC3\xA9\xED\xA0\xBE\xED\xB6\x80");
+        assert_eq!(jni.to_string_with(CodePage::Mutf8), "a\0\u{E9}\u{1F980}");
+        assert_eq!(jni.to_string_with(CodePage::Cesu8), "a\u{FFFD}\u{FFFD}\u{E9}\u{1F980}");
+        assert_eq!(jni.to_string_lossy().chars().filter(|ch| *ch == '\u{FFFD}').count(), 8);
+        assert_eq!(cstr!(b"\xED\xA0\xBEx\xED\xB6\x80").to_string_with(CodePage::Mutf8), "\u{FFFD}x\u{FFFD}", "unpaired surrogates");
+        assert_eq!(cstr!(b"\xF0\x9F\xA6\x80").to_string_with(CodePage::Mutf8), "\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}", "4 byte UTF8 is invalid MUTF-8");
+        assert_eq!(cstr!(b"\xE0\x80\x80\xC3").to_string_with(CodePage::Mutf8), "\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}", "overlong / truncated");
+        assert_eq!(cstr_mutf8!("a\0b\u{1F980}").to_string_with(CodePage::Mutf8), "a\0b\u{1F980}");
+    }
+
+    #[cfg(feature = "encoding_rs")] {
+        let mut buf = CStrBuf::<u8, 8>::default();
+        buf.set_with(encoding_rs::SHIFT_JIS.into(), "\u{65E5}\u{672C}").unwrap();
+        assert_eq!(buf.to_units(), b"\x93\xFA\x96\x7B");
+        assert_eq!(buf.to_string_with(encoding_rs::SHIFT_JIS.into()), "\u{65E5}\u{672C}");
+        assert_eq!(buf.set_with(encoding_rs::SHIFT_JIS.into(), "\u{65E5}\u{672C}\u{65E5}\u{672C}"), Err(EncodeError::BufferTooSmall(BufferTooSmallError::new(9, 8))));
+        assert_eq!(buf.set_with(encoding_rs::GBK.into(), "a\u{1F980}"), Err(EncodeError::Unmappable(UnmappableCharError::new('\u{1F980}', 1))));
+        assert_eq!(buf.set_with(encoding_rs::SHIFT_JIS.into(), "\u{65E5}\0"), Err(EncodeError::InteriorNul(InteriorNulError::new(3))));
+        assert_eq!(buf.to_units(), b"\x93\xFA\x96\x7B");
+        buf.set_with(encoding_rs::WINDOWS_1251.into(), "\u{416}").unwrap();
+        assert_eq!(buf.to_units(), b"\xC6");
+    }
+}
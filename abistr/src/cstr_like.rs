@@ -0,0 +1,132 @@
+use crate::*;
+
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+#[cfg(feature = "alloc")] use alloc::ffi::CString;
+use core::ffi::CStr;
+use core::iter::FusedIterator;
+use core::str::Utf8Error;
+
+
+
+/// Read-only access to any C string of [`Unit`]s: [`CStrPtr`], [`CStrNonNull`], [`CStrBuf`], [`CStr`], `U16CStr`, etc.
+///
+/// Lets helpers accept "any C string" without an overload per type:
+///
+/// ```
+/// # use abistr::*;
+/// # #[cfg(feature = "alloc")] {
+/// fn shout<U: Unit>(s: impl CStrLike<U>) -> String { s.chars_lossy().flat_map(char::to_uppercase).collect() }
+///
+/// assert_eq!(shout(cstr!("abc")), "ABC");
+/// assert_eq!(shout(&CStrBuf::<u16, 8>::from_truncate(&[0x64, 0x65, 0x66])), "DEF");
+/// assert_eq!(shout(core::ffi::CStr::from_bytes_with_nul(b"ghi\0").unwrap()), "GHI");
+/// # }
+/// ```
+///
+/// Strings are read as-is: [`CStrBuf`]s end at their first `\0` (or the end of the buffer), and <code>[null]\(\)</code> [`CStrPtr`]s are empty.
+///
+/// [null]: core::ptr::null
+pub trait CStrLike<U: Unit = u8> {
+    /// Get the code units of the string, **excluding** the terminal `\0`.
+    ///
+    /// `O(n)` to locate the terminal `\0` for most types.
+    fn to_units(&self) -> &[U];
+
+    /// Checks if the string is empty (e.g. the first character is `\0`.)
+    fn is_empty(&self) -> bool { self.to_units().is_empty() }
+
+    /// Decode the string into [`char`]s, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    fn chars_lossy(&self) -> CStrCharsLossy<'_, U> { CStrCharsLossy::new(self.to_units()) }
+
+    /// Convert the string to a <code>[Cow]\<[str]\></code>, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    #[cfg(feature = "alloc")]
+    fn to_string_lossy(&self) -> Cow<'_, str> { U::to_string_lossy(self.to_units()) }
+
+    /// Get a [`Display`](core::fmt::Display)able adapter for the string, which decodes without allocating.
+    fn display(&self) -> CStrDisplay<'_, U> { CStrDisplay::new(self.to_units()) }
+
+    /// Convert the string to a <code>&[str]</code>, returning <code>[Err]\([Utf8Error]\)</code> instead if it isn't valid UTF8.  Only available for [`u8`] strings.
+    fn to_str(&self) -> Result<&str, Utf8Error> where Self : CStrLike<u8> { core::str::from_utf8(CStrLike::<u8>::to_units(self)) }
+}
+
+impl<U: Unit, T: CStrLike<U> + ?Sized> CStrLike<U> for &'_ T {
+    fn to_units(&self) -> &[U] { T::to_units(self) }
+    fn is_empty(&self) -> bool { T::is_empty(self) }
+}
+
+impl<U: Unit> CStrLike<U> for CStrPtr<'_, U> {
+    fn to_units(&self) -> &[U] { CStrPtr::to_units(self) }
+    fn is_empty(&self) -> bool { CStrPtr::is_empty(self) }
+}
+
+impl<U: Unit> CStrLike<U> for CStrNonNull<'_, U> {
+    fn to_units(&self) -> &[U] { CStrNonNull::to_units(self) }
+    fn is_empty(&self) -> bool { CStrNonNull::is_empty(self) }
+}
+
+impl<U: Unit> CStrLike<U> for Option<CStrNonNull<'_, U>> {
+    fn to_units(&self) -> &[U] { self.as_ref().map_or(&[], |s| s.to_units()) }
+}
+
+impl<U: Unit, const N: usize> CStrLike<U> for CStrBuf<U, N> {
+    fn to_units(&self) -> &[U] { CStrBuf::to_units(self) }
+    fn is_empty(&self) -> bool { CStrBuf::is_empty(self) }
+}
+
+#[cfg(feature = "alloc")] impl<U: Unit> CStrLike<U> for SmallCString<U> {
+    fn to_units(&self) -> &[U] { SmallCString::to_units(self) }
+}
+
+impl CStrLike<u8> for CStr { fn to_units(&self) -> &[u8] { self.to_bytes() } }
+#[cfg(feature = "alloc")] impl CStrLike<u8> for CString { fn to_units(&self) -> &[u8] { self.as_bytes() } }
+
+#[cfg(feature = "widestring")] impl CStrLike<u16> for widestring::U16CStr { fn to_units(&self) -> &[u16] { self.as_slice() } }
+#[cfg(feature = "widestring")] impl CStrLike<u32> for widestring::U32CStr { fn to_units(&self) -> &[u32] { self.as_slice() } }
+#[cfg(feature = "widestring")] impl CStrLike<u16> for widestring::U16CString { fn to_units(&self) -> &[u16] { self.as_slice() } }
+#[cfg(feature = "widestring")] impl CStrLike<u32> for widestring::U32CString { fn to_units(&self) -> &[u32] { self.as_slice() } }
+
+
+
+/// Iterator over the [`char`]s of a C string, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].  Returned by [`CStrLike::chars_lossy`].
+#[derive(Clone)]
+pub struct CStrCharsLossy<'s, U: Unit> {
+    units: &'s [U],
+}
+
+impl<'s, U: Unit> CStrCharsLossy<'s, U> {
+    pub(crate) fn new(units: &'s [U]) -> Self { Self { units } }
+}
+
+impl<U: Unit> Iterator for CStrCharsLossy<'_, U> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        if self.units.is_empty() { return None; }
+        let (ch, n) = U::decode(self.units);
+        self.units = &self.units[n..];
+        Some(ch.unwrap_or(core::char::REPLACEMENT_CHARACTER))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) { ((self.units.len() + 3) / 4, Some(self.units.len())) }
+}
+
+impl<U: Unit> FusedIterator for CStrCharsLossy<'_, U> {}
+
+
+
+#[test] fn generic() {
+    fn summary<U: Unit>(s: &impl CStrLike<U>) -> (usize, bool, usize) { (s.to_units().len(), s.is_empty(), s.chars_lossy().count()) }
+
+    let cstr = CStr::from_bytes_with_nul(b"de\0").unwrap();
+    assert_eq!(summary(&cstr!("abc")),                                  (3, false, 3));
+    assert_eq!(summary(&CStrPtr::<u16>::NULL),                          (0, true,  0));
+    assert_eq!(summary(&Some(cstr16!("\u{1F980}"))),                    (2, false, 1));
+    assert_eq!(summary(&None::<CStrNonNull<u32>>),                      (0, true,  0));
+    assert_eq!(summary(&CStrBuf::<u8, 4>::from_truncate(b"\xFFab")),    (3, false, 3));
+    assert_eq!(summary(&cstr),                                          (2, false, 2));
+
+    assert!(cstr16!("a\u{1F980}").chars_lossy().eq(['a', '\u{1F980}'].iter().copied()));
+    assert!(CStrLike::chars_lossy(&CStrBuf::<u16, 3>::from_truncate(&[0xD800, 0x62])).eq(['\u{FFFD}', 'b'].iter().copied()));
+    assert_eq!(CStrLike::to_str(&cstr!("abc")), Ok("abc"));
+    assert!(CStrLike::to_str(&CStrBuf::<u8, 4>::from_truncate(b"\xFF")).is_err());
+    #[cfg(feature = "alloc")] assert_eq!(alloc::format!("{}", CStrLike::display(&cstr)), "de");
+    #[cfg(feature = "alloc")] assert_eq!(CStrLike::to_string_lossy(&CStrPtr::<u32>::NULL), "");
+}
@@ -0,0 +1,226 @@
+use crate::*;
+
+use core::convert::TryFrom;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::iter::FusedIterator;
+use core::str::Utf8Error;
+
+
+
+/// <code>[CUtf8NonNull]</code> is ABI compatible with <code>*const [c_char]</code>, and is guaranteed to point to valid UTF8.
+///
+/// Unlike <code>[CStrNonNull]<[Utf8]></code>, every constructor validates (or is `unsafe`), so [`as_str`](Self::as_str) has no error branch.
+/// Use [`cutf8!`] for literals, or [`from_cstr_non_null`](Self::from_cstr_non_null) to validate strings from C.
+///
+/// ```
+/// # use abistr::*;
+/// let s = cutf8!("\u{1F980}");
+/// assert_eq!(s.as_str(), "\u{1F980}");
+/// assert!(CUtf8NonNull::from_cstr_non_null(cstr!(b"\xFF")).is_err());
+/// ```
+///
+/// [c_char]: core::ffi::c_char
+#[repr(transparent)]
+#[derive(Clone, Copy, Default)]
+pub struct CUtf8NonNull<'s>(CStrNonNull<'s, u8>);
+
+impl<'s> CUtf8NonNull<'s> {
+    /// Validate that `s` is UTF8.
+    pub fn from_cstr_non_null(s: CStrNonNull<'s, u8>) -> Result<Self, Utf8Error> { core::str::from_utf8(s.to_units())?; Ok(Self(s)) }
+
+    /// Convert `s` (which must end with `\0`, and contain no interior `\0`s.)
+    pub fn from_str_with_nul(s: &'s str) -> Result<Self, FromUnitsWithNulError> { CStrNonNull::from_units_with_nul(s.as_bytes()).map(Self) }
+
+    /// ### Safety
+    /// *   `ptr` must be non-null, and point to a `\0`-terminated, valid UTF8 string.
+    /// *   The string must remain valid and immutable for the lifetime `'s`.
+    pub const unsafe fn from_ptr_unchecked_unbounded(ptr: *const c_char) -> Self { Self(CStrNonNull::from_ptr_unchecked_unbounded(ptr)) }
+
+    /// ### Safety
+    /// This function is marked as **safe**, but is not.  It must only be called by [`cutf8!`], which validates `units` at compile time.
+    #[doc(hidden)] // This fn only exists to allow the use of the totally safe `cutf8!` macro in `#![forbid(unsafe_code)]` codebases.
+    pub const fn zzz_unsound_do_not_call_this_directly_from_macro_units_with_nul(units: &'s [u8]) -> Self {
+        Self(CStrNonNull::zzz_unsound_do_not_call_this_directly_from_macro_units_with_nul(units))
+    }
+
+    /// Treat `self` as a raw C string.
+    pub const fn as_ptr(&self) -> *const c_char { self.0.as_ptr() }
+
+    /// Forget that `self` is known to be UTF8.
+    pub const fn as_cstr_non_null(&self) -> CStrNonNull<'s, u8> { self.0 }
+
+    /// Checks if `self` is empty (e.g. the first character is `\0`.)
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Get the bytes of the string, **excluding** the terminal `\0`.  `O(n)` to locate the terminal `\0`.
+    pub fn to_bytes(&self) -> &'s [u8] { self.0.to_units() }
+
+    /// Borrow the string as a [`str`].  `O(n)` to locate the terminal `\0`.
+    pub fn as_str(&self) -> &'s str { unsafe { core::str::from_utf8_unchecked(self.to_bytes()) } }
+}
+
+impl Debug   for CUtf8NonNull<'_> { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { Debug::fmt(self.as_str(), fmt) } }
+impl Display for CUtf8NonNull<'_> { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { Display::fmt(self.as_str(), fmt) } }
+
+impl<'s> From<CUtf8NonNull<'s>> for CStrNonNull<'s, u8>     { fn from(s: CUtf8NonNull<'s>) -> Self { s.0 } }
+impl<'s> From<CUtf8NonNull<'s>> for CStrNonNull<'s, Utf8>   { fn from(s: CUtf8NonNull<'s>) -> Self { unsafe { CStrNonNull::from_ptr_unchecked_unbounded(s.as_ptr()) } } }
+impl<'s> From<CUtf8NonNull<'s>> for &'s str                 { fn from(s: CUtf8NonNull<'s>) -> Self { s.as_str() } }
+impl<'s> TryFrom<CStrNonNull<'s, u8>> for CUtf8NonNull<'s>  { type Error = Utf8Error; fn try_from(s: CStrNonNull<'s, u8>) -> Result<Self, Self::Error> { Self::from_cstr_non_null(s) } }
+
+unsafe impl AsCStr<u8  > for CUtf8NonNull<'_> { fn as_cstr(&self) -> *const c_char { self.as_ptr() } }
+unsafe impl AsCStr<Utf8> for CUtf8NonNull<'_> { fn as_cstr(&self) -> *const c_char { self.as_ptr() } }
+impl CStrLike<u8> for CUtf8NonNull<'_> { fn to_units(&self) -> &[u8] { self.to_bytes() } }
+
+
+
+/// <code>[CUtf16NonNull]</code> is ABI compatible with <code>*const [u16]</code>, and is guaranteed to point to valid UTF16 (no unpaired surrogates.)
+///
+/// Use [`cutf16!`] for literals, or [`from_cstr_non_null`](Self::from_cstr_non_null) to validate strings from C.
+/// [`chars`](Self::chars) and [`Display`] then decode without any replacement characters.
+///
+/// ```
+/// # use abistr::*;
+/// let s = cutf16!("\u{1F980}!");
+/// assert!(s.chars().eq("\u{1F980}!".chars()));
+/// let unpaired = CStrNonNull::<u16>::from_units_with_nul(&[0x61, 0xD83E, 0]).unwrap();
+/// assert_eq!(CUtf16NonNull::from_cstr_non_null(unpaired).unwrap_err().valid_up_to(), 1);
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy, Default)]
+pub struct CUtf16NonNull<'s>(CStrNonNull<'s, u16>);
+
+impl<'s> CUtf16NonNull<'s> {
+    /// Validate that `s` is UTF16 (contains no unpaired surrogates.)
+    pub fn from_cstr_non_null(s: CStrNonNull<'s, u16>) -> Result<Self, Utf16Error> {
+        let mut valid_up_to = 0;
+        for ch in core::char::decode_utf16(s.to_units().iter().copied()) {
+            valid_up_to += ch.map_err(|_| Utf16Error::new(valid_up_to))?.len_utf16();
+        }
+        Ok(Self(s))
+    }
+
+    /// ### Safety
+    /// *   `ptr` must be non-null, and point to a `\0`-terminated, valid UTF16 string.
+    /// *   The string must remain valid and immutable for the lifetime `'s`.
+    pub const unsafe fn from_ptr_unchecked_unbounded(ptr: *const u16) -> Self { Self(CStrNonNull::from_ptr_unchecked_unbounded(ptr)) }
+
+    /// ### Safety
+    /// This function is marked as **safe**, but is not.  It must only be called by [`cutf16!`], which validates `units` at compile time.
+    #[doc(hidden)] // This fn only exists to allow the use of the totally safe `cutf16!` macro in `#![forbid(unsafe_code)]` codebases.
+    pub const fn zzz_unsound_do_not_call_this_directly_from_macro_units_with_nul(units: &'s [u16]) -> Self {
+        Self(CStrNonNull::zzz_unsound_do_not_call_this_directly_from_macro_units_with_nul(units))
+    }
+
+    /// Treat `self` as a raw C string.
+    pub const fn as_ptr(&self) -> *const u16 { self.0.as_ptr() }
+
+    /// Forget that `self` is known to be UTF16.
+    pub const fn as_cstr_non_null(&self) -> CStrNonNull<'s, u16> { self.0 }
+
+    /// Checks if `self` is empty (e.g. the first character is `\0`.)
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Get the units of the string, **excluding** the terminal `\0`.  `O(n)` to locate the terminal `\0`.
+    pub fn to_units(&self) -> &'s [u16] { self.0.to_units() }
+
+    /// Decode the string into [`char`]s.
+    pub fn chars(&self) -> CUtf16Chars<'s> { CUtf16Chars { units: self.to_units() } }
+}
+
+impl Debug   for CUtf16NonNull<'_> { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { Debug::fmt(&self.0, fmt) } }
+impl Display for CUtf16NonNull<'_> { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { self.chars().try_for_each(|ch| fmt::Write::write_char(fmt, ch)) } }
+
+impl<'s> From<CUtf16NonNull<'s>> for CStrNonNull<'s, u16>       { fn from(s: CUtf16NonNull<'s>) -> Self { s.0 } }
+impl<'s> TryFrom<CStrNonNull<'s, u16>> for CUtf16NonNull<'s>    { type Error = Utf16Error; fn try_from(s: CStrNonNull<'s, u16>) -> Result<Self, Self::Error> { Self::from_cstr_non_null(s) } }
+
+unsafe impl AsCStr<u16> for CUtf16NonNull<'_> { fn as_cstr(&self) -> *const u16 { self.as_ptr() } }
+impl CStrLike<u16> for CUtf16NonNull<'_> { fn to_units(&self) -> &[u16] { CUtf16NonNull::to_units(self) } }
+
+
+
+/// Iterator over the [`char`]s of a [`CUtf16NonNull`].
+#[derive(Clone)]
+pub struct CUtf16Chars<'s> {
+    units: &'s [u16],
+}
+
+impl Iterator for CUtf16Chars<'_> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        let (&lead, rest) = self.units.split_first()?;
+        let (ch, rest) = match lead {
+            0xD800 ..= 0xDBFF => {
+                let (&trail, rest) = rest.split_first().unwrap_or((&0xDC00, rest)); // validated: `trail` always exists
+                (0x10000 + ((u32::from(lead) - 0xD800) << 10) + (u32::from(trail) - 0xDC00), rest)
+            },
+            _ => (u32::from(lead), rest),
+        };
+        self.units = rest;
+        Some(unsafe { core::char::from_u32_unchecked(ch) })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) { ((self.units.len() + 1) / 2, Some(self.units.len())) }
+}
+
+impl FusedIterator for CUtf16Chars<'_> {}
+
+
+
+#[test] fn cutf() {
+    assert_abi_compatible!(CUtf8NonNull, *const c_char);
+    assert_abi_compatible!(Option<CUtf8NonNull>, *const c_char);
+    assert_abi_compatible!(CUtf16NonNull, *const u16);
+    assert_abi_compatible!(Option<CUtf16NonNull>, *const u16);
+
+    const _C : CUtf8NonNull<'static> = cutf8!("C");
+    assert_eq!(cutf8!("").as_str(), "");
+    assert_eq!(cutf8!("a\u{E9}\u{1F980}").as_str(), "a\u{E9}\u{1F980}");
+    assert_eq!(CUtf8NonNull::default().as_str(), "");
+    assert_eq!(CUtf8NonNull::from_cstr_non_null(cstr!("abc")).unwrap().as_str(), "abc");
+    assert_eq!(CUtf8NonNull::from_cstr_non_null(cstr!(b"a\xC3")).unwrap_err().valid_up_to(), 1);
+    assert_eq!(CUtf8NonNull::from_str_with_nul("ab\0").unwrap().to_bytes(), b"ab");
+    assert!(CUtf8NonNull::from_str_with_nul("ab").is_err());
+    assert_eq!(CStrNonNull::<Utf8>::from(cutf8!("xyz")).as_str(), "xyz");
+
+    const _C16 : CUtf16NonNull<'static> = cutf16!("C");
+    assert!(cutf16!("").chars().eq("".chars()));
+    assert!(cutf16!("a\u{E9}\u{1F980}b").chars().eq("a\u{E9}\u{1F980}b".chars()));
+    assert!(CUtf16NonNull::default().is_empty());
+    assert!(CUtf16NonNull::from_cstr_non_null(cstr16!("\u{1F980}")).is_ok());
+    let lone_trail = CStrNonNull::<u16>::from_units_with_nul(&[0x61, 0x62, 0xDC00, 0x63, 0]).unwrap();
+    assert_eq!(CUtf16NonNull::try_from(lone_trail).unwrap_err(), Utf16Error::new(2));
+    let lone_lead = CStrNonNull::<u16>::from_units_with_nul(&[0xD83E, 0]).unwrap();
+    assert_eq!(CUtf16NonNull::try_from(lone_lead).unwrap_err(), Utf16Error::new(0));
+
+    #[cfg(feature = "alloc")] {
+        use alloc::string::ToString;
+        assert_eq!(cutf8!("a\u{1F980}").to_string(), "a\u{1F980}");
+        assert_eq!(cutf16!("a\u{1F980}").to_string(), "a\u{1F980}");
+        assert_eq!(alloc::format!("{:?}", cutf8!("a\"b")), "\"a\\\"b\"");
+        assert_eq!(with_cstr(cutf16!("ab"), |s: CStrNonNull<u16>| s.to_units().len()), Ok(2));
+    }
+}
+
+#[cfg(feature = "std")] #[allow(dead_code)] mod compile_tests {
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let _ = cutf8!(b"\xFF");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let _ = cutf8!(br"abc");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let _ = cutf16!(b"abc");
+    /// ```
+    struct ByteStrings;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// fn f(_: CUtf8NonNull) {}
+    /// f(cstr!("abc"));
+    /// ```
+    struct Unvalidated;
+}
@@ -0,0 +1,135 @@
+//! Support code for the `#[derive(...)]`s in `abistr-macros`, and [`ccall!`].  Not part of the public API.
+#![allow(missing_docs)]
+
+use crate::*;
+
+#[cfg(feature = "alloc")] pub use alloc::{borrow::Cow, boxed::Box, rc::Rc, string::String, sync::Arc};
+pub use core::str::Utf8Error;
+use core::marker::PhantomData;
+
+
+
+/// A C string field of a `#[derive(CStrAccessors)]` struct.
+pub trait CStrField {
+    type Unit : Unit;
+    fn units(&self) -> &[Self::Unit];
+}
+
+impl<U: Unit, const N: usize> CStrField for CStrBuf<U, N>           { type Unit = U; fn units(&self) -> &[U] { self.to_units() } }
+impl<U: Unit> CStrField for CStrPtr<'_, U>                          { type Unit = U; fn units(&self) -> &[U] { self.to_units() } }
+impl<U: Unit> CStrField for CStrNonNull<'_, U>                      { type Unit = U; fn units(&self) -> &[U] { self.to_units() } }
+impl<U: Unit> CStrField for Option<CStrNonNull<'_, U>>              { type Unit = U; fn units(&self) -> &[U] { self.as_ref().map_or(&[], |s| s.to_units()) } }
+
+#[cfg(feature = "alloc")]
+pub fn to_string_lossy<F: CStrField + ?Sized>(field: &F) -> Cow<'_, str> { Unit::to_string_lossy(field.units()) }
+pub fn to_str<F: CStrField<Unit = u8> + ?Sized>(field: &F) -> Result<&str, Utf8Error> { core::str::from_utf8(field.units()) }
+pub fn display<F: CStrField + ?Sized>(field: &F) -> CStrDisplay<'_, F::Unit> { CStrDisplay::new(field.units()) }
+
+
+
+#[cfg(feature = "alloc")] pub use crate::ffi::{FfiStorage, IntoFfiField, FromFfiField};
+#[cfg(feature = "alloc")] pub fn ffi_guard<T: IntoFfi + ?Sized>(raw: T::Ffi<'static>, storage: FfiStorage) -> FfiGuard<T> { FfiGuard::new(raw, storage) }
+pub fn into_ffi_error(field: &'static str, kind: IntoFfiErrorKind) -> IntoFfiError { IntoFfiError::new(field, kind) }
+pub fn from_ffi_error(field: &'static str, kind: FromFfiErrorKind) -> FromFfiError { FromFfiError::new(field, kind) }
+
+
+
+/// Maps the `*const C` a [`ccall!`] callee expects back to the [`Unit`] to convert `@cstr` / `@opt_cstr` arguments to.
+/// `*const i8` maps to [`u8`], as that's usually `*const c_char`.
+pub trait CallChar : Copy { type Unit : Unit; }
+impl CallChar for i8  { type Unit = u8;  }
+impl CallChar for u8  { type Unit = u8;  }
+impl CallChar for u16 { type Unit = u16; }
+impl CallChar for u32 { type Unit = u32; }
+impl CallChar for i16 { type Unit = i16; }
+impl CallChar for i32 { type Unit = i32; }
+
+/// A converted `@cstr` argument of [`ccall!`], tying the `U` of [`TryIntoAsCStr`] to the `*const C` the callee expects.
+pub struct CStrArg<C, T>(T, PhantomData<fn() -> C>);
+impl<C: CallChar, T: AsCStr<C::Unit>> CStrArg<C, T> { pub fn as_ptr(&self) -> *const C { self.0.as_cstr().cast() } }
+
+/// A converted `@opt_cstr` argument of [`ccall!`], tying the `U` of [`TryIntoAsOptCStr`] to the `*const C` the callee expects.
+pub struct OptCStrArg<C, T>(T, PhantomData<fn() -> C>);
+impl<C: CallChar, T: AsOptCStr<C::Unit>> OptCStrArg<C, T> { pub fn as_ptr(&self) -> *const C { self.0.as_opt_cstr().cast() } }
+
+pub fn cstr_arg<C: CallChar, S: TryIntoAsCStr<C::Unit>>(index: usize, arg: &'static str, s: S) -> Result<CStrArg<C, S::Target>, CallArgError> where S::Error : Into<FromUnitsWithNulError> {
+    s.try_into().map(|t| CStrArg(t, PhantomData)).map_err(|err| CallArgError::new(index, arg, err.into()))
+}
+
+pub fn opt_cstr_arg<C: CallChar, S: TryIntoAsOptCStr<C::Unit>>(index: usize, arg: &'static str, s: S) -> Result<OptCStrArg<C, S::Target>, CallArgError> where S::Error : Into<FromUnitsWithNulError> {
+    s.try_into().map(|t| OptCStrArg(t, PhantomData)).map_err(|err| CallArgError::new(index, arg, err.into()))
+}
+
+
+
+#[cfg(feature = "std")]
+#[test] fn cstr_accessors() {
+    #[derive(CStrAccessors, Default)]
+    #[abistr(debug)]
+    struct Generic<'s, T: Copy + Default = u8, const N: usize = 8> where T: core::fmt::Debug {
+        #[abistr(get = "display", set)]             wide:   CStrBuf<u16, N>,
+        #[abistr(get)]                              ptr:    Option<CStrNonNull<'s>>,
+        #[abistr(get = "str", rename = "text")]     nonnull: CStrNonNull<'s>,
+        extra: T,
+    }
+
+    let mut g = Generic::<u32, 4>::default();
+    assert_eq!(g.set_wide("abc"), Ok(()));
+    assert_eq!(g.set_wide("abcd"), Err(BufferTooSmallError::new(5, 4).into()));
+    assert_eq!(g.wide().to_string(), "abc");
+    assert_eq!(g.ptr(), "");
+    g.ptr = Some(cstr!("ptr"));
+    assert_eq!(g.ptr(), "ptr");
+    assert_eq!(g.text(), Ok(""));
+    g.extra = 42;
+    assert_eq!(format!("{:?}", g), "Generic { wide: \"abc\", ptr: \"ptr\", nonnull: \"\", extra: 42 }");
+
+    #[derive(CStrAccessors, Default)]
+    #[abistr(debug, crate = "crate")]
+    struct Tuple(#[abistr(get, set = "checked", rename = "name")] CStrBuf<u8, 4>, u8);
+
+    let mut t = Tuple::default();
+    assert_eq!(t.set_name("abc"), Ok(()));
+    assert_eq!(t.set_name("abcd"), Err(BufferTooSmallError::new(5, 4).into()));
+    assert_eq!(t.set_name("a\0"), Err(InteriorNulError::new(1).into()));
+    assert_eq!(t.name(), "abc");
+    assert_eq!(format!("{:?}", t), "Tuple(\"abc\", 0)");
+}
+
+#[cfg(feature = "std")] #[allow(dead_code)] mod compile_tests {
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] struct S { #[abistr(gte)] a: CStrBuf<u8, 8> }
+    /// ```
+    struct UnknownKey;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] struct S { #[abistr(get = "strict")] a: CStrBuf<u8, 8> }
+    /// ```
+    struct UnknownGetter;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] struct S(#[abistr(get)] CStrBuf<u8, 8>);
+    /// ```
+    struct TupleWithoutRename;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] enum E { A }
+    /// ```
+    struct Enum;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] struct S { #[abistr(get = "str")] a: CStrBuf<u16, 8> }
+    /// ```
+    struct StrOfWide;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(CStrAccessors)] struct S<'s> { #[abistr(set)] a: CStrPtr<'s> }
+    /// ```
+    struct SetPtr;
+}
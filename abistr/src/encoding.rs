@@ -0,0 +1,246 @@
+use crate::*;
+
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+use core::convert::TryFrom;
+use core::fmt::{self, Formatter, LowerHex};
+use core::str::Utf8Error;
+
+
+
+/// A [`u8`] [`Unit`] known to be part of valid UTF8: <code>[CStrPtr]<[Utf8]></code> etc. can be borrowed [`as_str`](CStrPtr::as_str) without a fallible conversion.
+///
+/// Plain [`u8`] units make no promises about their encoding (they might be UTF8, ASCII, Latin-1, or arbitrary bytes.)
+/// [`Utf8`] strings are validated on entry (e.g. via [`TryFrom`]), and can't be accidentally mixed with other encodings.
+/// [`Utf8`] isn't [`AnyUnitSequence`], so strings can't be built from raw <code>&\[[Utf8]\]</code>s either (which might have been sliced mid-character):
+///
+/// ```
+/// # use abistr::*;
+/// # use core::convert::TryFrom;
+/// fn title(s: CStrNonNull<Utf8>) -> &str { s.as_str() }
+///
+/// let bytes = cstr!("example");
+/// assert_eq!(title(CStrNonNull::try_from(bytes).unwrap()), "example");
+/// assert!(CStrNonNull::<Utf8>::try_from(cstr8!(b"\xFF")).is_err());
+/// ```
+///
+/// [`Utf8`] is ABI compatible with [`u8`], and [`Unit::CChar`] is still [`c_char`](core::ffi::c_char).
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Utf8(u8);
+
+/// A [`u8`] [`Unit`] known to be ASCII (`0x00 ..= 0x7F`): <code>[CStrPtr]<[Ascii]></code> etc. can be borrowed [`as_str`](CStrPtr::as_str) without a fallible conversion.
+///
+/// Fallible conversions from [`str`] (e.g. [`CStrBuf::try_set_str`], or [`TryIntoAsCStr`]) fail with a [`NotAsciiError`] on non-ASCII characters,
+/// while lossy or truncating ones (e.g. [`CStrBuf::set_str_truncate`], or [`Lossy`]) replace them with `?`.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Ascii(u8);
+
+impl Utf8 {
+    /// The underlying byte.
+    pub const fn get(self) -> u8 { self.0 }
+
+    /// Reinterpret `s` as [`Utf8`] units.
+    pub fn slice_from_str(s: &str) -> &[Utf8] { unsafe { cast_slice(s.as_bytes()) } }
+
+    /// Reinterpret `bytes` as [`Utf8`] units, or fail if they aren't valid UTF8.
+    pub fn slice_from_bytes(bytes: &[u8]) -> Result<&[Utf8], Utf8Error> { core::str::from_utf8(bytes).map(Self::slice_from_str) }
+
+    /// Reinterpret `units` as bytes.
+    pub fn slice_to_bytes(units: &[Utf8]) -> &[u8] { unsafe { cast_slice(units) } }
+}
+
+impl Ascii {
+    /// The underlying byte.
+    pub const fn get(self) -> u8 { self.0 }
+
+    /// Convert `byte`, or return [`None`] if it isn't ASCII.
+    pub const fn new(byte: u8) -> Option<Self> { if byte < 0x80 { Some(Self(byte)) } else { None } }
+
+    /// Reinterpret `bytes` as [`Ascii`] units, or fail if they aren't all ASCII.
+    pub fn slice_from_bytes(bytes: &[u8]) -> Result<&[Ascii], NotAsciiError> {
+        match bytes.iter().position(|b| *b >= 0x80) {
+            Some(position)  => Err(NotAsciiError::new(position)),
+            None            => Ok(unsafe { cast_slice(bytes) }),
+        }
+    }
+
+    /// Reinterpret `units` as bytes.
+    pub fn slice_to_bytes(units: &[Ascii]) -> &[u8] { unsafe { cast_slice(units) } }
+
+    /// Borrow `units` as a [`str`] (ASCII is always valid UTF8.)
+    pub fn slice_to_str(units: &[Ascii]) -> &str { unsafe { core::str::from_utf8_unchecked(Self::slice_to_bytes(units)) } }
+}
+
+impl LowerHex for Utf8  { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { LowerHex::fmt(&self.0, fmt) } }
+impl LowerHex for Ascii { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { LowerHex::fmt(&self.0, fmt) } }
+
+impl From<Utf8>  for u8 { fn from(unit: Utf8 ) -> u8 { unit.0 } }
+impl From<Ascii> for u8 { fn from(unit: Ascii) -> u8 { unit.0 } }
+impl From<Ascii> for Utf8 { fn from(unit: Ascii) -> Utf8 { Utf8(unit.0) } }
+
+unsafe impl Unit for Utf8 {
+    type CChar = c_char;
+    type StrError = InteriorNulError;
+    const NUL : Self = Utf8(0);
+    const EMPTY : &'static [Self; 1] = &[Utf8(0)];
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { u8::debug_unit(unit.0, fmt) }
+    fn decode(units: &[Self]) -> (Option<char>, usize) { u8::decode(Self::slice_to_bytes(units)) }
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { let n = ch.len_utf8(); ch.encode_utf8(unsafe { &mut *(buf as *mut [Self; 4] as *mut [u8; 4]) }); &buf[..n] }
+    #[cfg(feature = "alloc")] fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { u8::to_string_lossy(Self::slice_to_bytes(units)) }
+}
+
+unsafe impl Unit for Ascii {
+    type CChar = c_char;
+    type StrError = AsciiStrError;
+    const NUL : Self = Ascii(0);
+    const EMPTY : &'static [Self; 1] = &[Ascii(0)];
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { u8::debug_unit(unit.0, fmt) }
+    fn decode(units: &[Self]) -> (Option<char>, usize) { (Some(char::from(units[0].0)), 1) }
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { buf[0] = if ch.is_ascii() { Ascii(ch as u8) } else { Ascii(b'?') }; &buf[..1] }
+    fn check_char(ch: char, position: usize) -> Result<(), Self::StrError> { if ch.is_ascii() { Ok(()) } else { Err(NotAsciiError::new(position).into()) } }
+    #[cfg(feature = "alloc")] fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { Cow::Borrowed(Self::slice_to_str(units)) }
+}
+
+unsafe impl AnyUnitSequence for Ascii {} // every unit is a complete character
+
+/// ### Safety
+/// `T` and `U` must be `#[repr(transparent)]` wrappers around (or the same as) [`u8`], and every byte of `T` must be a valid `U`.
+unsafe fn cast_slice<T, U>(units: &[T]) -> &[U] { core::slice::from_raw_parts(units.as_ptr().cast(), units.len()) }
+
+
+
+macro_rules! encoded {
+    ( $( $ptr:ident )* ) => {$(
+        impl<'s> $ptr<'s, Utf8> {
+            /// Borrow the string as a [`str`] (`O(n)` to locate the terminal `\0`.)
+            pub fn as_str(&self) -> &'s str { unsafe { core::str::from_utf8_unchecked(self.to_bytes()) } } // ✔️ constructors validate (or are `unsafe`), and `Utf8` isn't `AnyUnitSequence`
+
+            /// Get the bytes of the string, **excluding** the terminal `\0`.
+            pub fn to_bytes(&self) -> &'s [u8] { Utf8::slice_to_bytes(self.to_units()) }
+        }
+
+        impl<'s> $ptr<'s, Ascii> {
+            /// Borrow the string as a [`str`] (`O(n)` to locate the terminal `\0`.)
+            pub fn as_str(&self) -> &'s str { Ascii::slice_to_str(self.to_units()) }
+
+            /// Get the bytes of the string, **excluding** the terminal `\0`.
+            pub fn to_bytes(&self) -> &'s [u8] { Ascii::slice_to_bytes(self.to_units()) }
+        }
+
+        impl<'s> From<$ptr<'s, Utf8>>  for $ptr<'s, u8>   { fn from(s: $ptr<'s, Utf8> ) -> Self { unsafe { Self::cast(s) } } }
+        impl<'s> From<$ptr<'s, Ascii>> for $ptr<'s, u8>   { fn from(s: $ptr<'s, Ascii>) -> Self { unsafe { Self::cast(s) } } }
+        impl<'s> From<$ptr<'s, Ascii>> for $ptr<'s, Utf8> { fn from(s: $ptr<'s, Ascii>) -> Self { unsafe { Self::cast(s) } } }
+
+        impl<'s> TryFrom<$ptr<'s, u8>> for $ptr<'s, Utf8> {
+            type Error = Utf8Error;
+            fn try_from(s: $ptr<'s, u8>) -> Result<Self, Self::Error> { Utf8::slice_from_bytes(s.to_units())?; Ok(unsafe { Self::cast(s) }) }
+        }
+
+        impl<'s> TryFrom<$ptr<'s, u8>> for $ptr<'s, Ascii> {
+            type Error = NotAsciiError;
+            fn try_from(s: $ptr<'s, u8>) -> Result<Self, Self::Error> { Ascii::slice_from_bytes(s.to_units())?; Ok(unsafe { Self::cast(s) }) }
+        }
+
+        impl<'s> TryFrom<$ptr<'s, Utf8>> for $ptr<'s, Ascii> {
+            type Error = NotAsciiError;
+            fn try_from(s: $ptr<'s, Utf8>) -> Result<Self, Self::Error> { Ascii::slice_from_bytes(s.to_bytes())?; Ok(unsafe { Self::cast(s) }) }
+        }
+    )*};
+}
+
+encoded!(CStrPtr CStrNonNull);
+
+impl<'s, U: Unit<CChar = c_char>> CStrPtr<'s, U> {
+    /// ### Safety
+    /// `s` must be valid as a `Self`.
+    unsafe fn cast<S: Unit<CChar = c_char>>(s: CStrPtr<'s, S>) -> Self { Self::from_ptr_unbounded(s.as_ptr()) }
+}
+
+impl<'s, U: Unit<CChar = c_char>> CStrNonNull<'s, U> {
+    /// ### Safety
+    /// `s` must be valid as a `Self`.
+    unsafe fn cast<S: Unit<CChar = c_char>>(s: CStrNonNull<'s, S>) -> Self { Self::from_ptr_unchecked_unbounded(s.as_ptr()) }
+}
+
+
+
+#[test] fn encodings() {
+    let abc = CStrNonNull::<Utf8>::try_from(cstr!("abc")).unwrap();
+    assert_eq!(abc.as_str(), "abc");
+    assert_eq!(CStrNonNull::<u8>::from(abc).to_units(), b"abc");
+    assert_eq!(CStrPtr::<Utf8>::default().as_str(), "");
+    assert_eq!(CStrPtr::<Ascii>::NULL.as_str(), "");
+
+    let crab = cstr8!("\u{1F980}");
+    assert_eq!(CStrNonNull::<Utf8>::try_from(crab).unwrap().as_str(), "\u{1F980}");
+    assert_eq!(CStrNonNull::<Ascii>::try_from(crab).unwrap_err(), NotAsciiError::new(0));
+    assert_eq!(CStrNonNull::<Ascii>::try_from(CStrNonNull::<Utf8>::try_from(crab).unwrap()).unwrap_err().position(), 0);
+    assert!(CStrPtr::<Utf8>::try_from(CStrPtr::from_units_with_nul(b"a\xFF\0").unwrap()).is_err());
+    assert_eq!(CStrPtr::<Ascii>::try_from(CStrPtr::from_units_with_nul(b"abc\0").unwrap()).unwrap().as_str(), "abc");
+    assert_eq!(CStrNonNull::<Utf8>::from(CStrNonNull::<Ascii>::try_from(cstr!("abc")).unwrap()).as_str(), "abc");
+
+    assert_eq!(Utf8::slice_from_bytes(b"\xC3\xA9").map(Utf8::slice_to_bytes), Ok(&b"\xC3\xA9"[..]));
+    assert_eq!(Ascii::slice_from_bytes(b"ab\xC3\xA9").unwrap_err().position(), 2);
+    assert_eq!(Ascii::new(0x80), None);
+
+    let mut buf = CStrBuf::<Ascii, 8>::default();
+    buf.set_str_truncate("caf\u{E9}").unwrap();
+    assert_eq!(Ascii::slice_to_str(buf.to_units()), "caf?");
+    assert_eq!(buf.try_set_str("na\u{EF}ve"), Err(SetStrError::NotAscii(NotAsciiError::new(2))));
+    assert_eq!(Ascii::slice_to_str(buf.to_units()), "caf?");
+    #[cfg(feature = "alloc")] assert_eq!(SmallCString::<Ascii>::new("caf\u{E9}").unwrap_err(), AsciiStrError::NotAscii(NotAsciiError::new(3)));
+    #[cfg(feature = "alloc")] assert_eq!(SmallCString::<Ascii>::new(&"a".repeat(100)).unwrap().to_units().len(), 100);
+    #[cfg(feature = "alloc")] assert_eq!(SmallCString::<Ascii>::new(&("a".repeat(100) + "\u{E9}")).unwrap_err(), AsciiStrError::NotAscii(NotAsciiError::new(100)));
+    #[cfg(feature = "alloc")] assert_eq!(Lossy("caf\u{E9}").into_small_cstring::<Ascii>().as_non_null().as_str(), "caf?");
+    let mut buf = CStrBuf::<Utf8, 8>::default();
+    assert!(buf.set_str_truncate("\u{1F980}\u{1F980}").is_err());
+    assert_eq!(buf.as_cstr_non_null().unwrap().as_str(), "\u{1F980}");
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("\u{E9}", |s: CStrNonNull<Utf8>| s.as_str().len()).unwrap(), 2);
+}
+
+#[cfg(feature = "std")] #[allow(dead_code)] mod compile_tests {
+    /// ```compile_fail
+    /// use abistr::*;
+    /// fn title(s: CStrNonNull<Utf8>) -> &str { s.as_str() }
+    /// title(cstr!("example"));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let _ = CStrNonNull::<u8>::default().as_str();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let ascii : CStrPtr<Ascii> = CStrPtr::<Utf8>::default().into();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let ascii = CStrPtrUnaligned::<Ascii>::from_bytes_with_nul(b"\xFF\xFE\0");
+    /// ```
+    struct MismatchedEncodings;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let s = CStrNonNull::<Utf8>::from_units_with_nul(&Utf8::slice_from_str("\u{E9}\0")[1..]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let buf = CStrBuf::<Utf8, 2>::from_truncate(Utf8::slice_from_str("\u{E9}"));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let mut buf = CStrBuf::<Utf8, 4>::default();
+    /// let _ = buf.try_set(&Utf8::slice_from_str("\u{E9}")[..1]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let s = SmallCString::<Utf8>::from_units(&Utf8::slice_from_str("\u{E9}")[1..]);
+    /// ```
+    struct Utf8FromRawUnits;
+}
@@ -0,0 +1,131 @@
+use crate::*;
+
+use core::fmt::{self, Formatter, LowerHex};
+
+
+
+macro_rules! endian_units {
+    ( $( $(#[$attr:meta])* $ty:ident ( $native:ty ; $n:literal ) : $from:ident $to:ident, $escape:literal, $max_units:literal; )* ) => {$(
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $ty([u8; $n]);
+
+        impl $ty {
+            /// Convert a native endian `value`.
+            pub const fn new(value: $native) -> Self { Self(value.$to()) }
+
+            /// The native endian value of the unit.
+            pub const fn get(self) -> $native { <$native>::$from(self.0) }
+
+            /// Reinterpret `bytes` as units, or return [`None`] if `bytes.len()` isn't a multiple of the unit size.
+            pub fn slice_from_bytes(bytes: &[u8]) -> Option<&[Self]> {
+                if bytes.len() % $n != 0 { return None }
+                Some(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len() / $n) })
+            }
+
+            /// Reinterpret `units` as bytes.
+            pub fn slice_to_bytes(units: &[Self]) -> &[u8] { unsafe { core::slice::from_raw_parts(units.as_ptr().cast(), units.len() * $n) } }
+        }
+
+        impl From<$native> for $ty { fn from(value: $native) -> Self { Self::new(value) } }
+        impl From<$ty> for $native { fn from(unit: $ty) -> Self { unit.get() } }
+
+        impl LowerHex for $ty { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { LowerHex::fmt(&self.get(), fmt) } }
+        impl fmt::Debug for $ty { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, concat!(stringify!($ty), "(0x{:x})"), self.get()) } }
+
+        unsafe impl Unit for $ty {
+            type CChar = Self;
+            type StrError = InteriorNulError;
+            const NUL : Self = Self([0; $n]);
+            const EMPTY : &'static [Self; 1] = &[Self([0; $n])];
+            fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, $escape, unit) }
+            fn decode(units: &[Self]) -> (Option<char>, usize) {
+                let mut native = [0; $max_units];
+                let n = units.len().min($max_units);
+                for (dst, src) in native.iter_mut().zip(units) { *dst = src.get(); }
+                <$native>::decode(&native[..n])
+            }
+            fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] {
+                let mut native = [0; 4];
+                let n = <$native>::encode_char(ch, &mut native).len();
+                for (dst, src) in buf.iter_mut().zip(native.iter()) { *dst = Self::new(*src); }
+                &buf[..n]
+            }
+        }
+
+        unsafe impl AnyUnitSequence for $ty {}
+
+        #[cfg(feature = "bytemuck")] unsafe impl bytemuck::Zeroable for $ty {}
+        #[cfg(feature = "bytemuck")] unsafe impl bytemuck::Pod      for $ty {}
+    )*};
+}
+
+endian_units! {
+    /// A little endian UTF16 [`Unit`], regardless of the native byte order (e.g. PE resources, most `UTF-16LE` file formats.)
+    ///
+    /// Unlike [`u16`], [`U16Le`] is stored as bytes, and has an alignment of `1`: it can be borrowed directly out of file contents.
+    ///
+    /// ```
+    /// # use abistr::*;
+    /// let bytes = b"h\0i\0\0\0";
+    /// let s = CStrNonNull::<U16Le>::from_units_with_nul(U16Le::slice_from_bytes(bytes).unwrap()).unwrap();
+    /// assert!(s.chars_lossy().eq("hi".chars()));
+    /// assert_eq!(format!("{:?}", s), "\"hi\"");
+    /// ```
+    U16Le(u16; 2) : from_le_bytes to_le_bytes, "\\u{:04x}", 2;
+
+    /// A big endian UTF16 [`Unit`], regardless of the native byte order (e.g. `UTF-16BE` protocol fields, TrueType/OpenType `name` tables.)
+    ///
+    /// Unlike [`u16`], [`U16Be`] is stored as bytes, and has an alignment of `1`: it can be borrowed directly out of file contents.
+    ///
+    /// ```
+    /// # use abistr::*;
+    /// let mut buf = CStrBuf::<U16Be, 4>::default();
+    /// buf.set_str_truncate("\u{1F980}").unwrap();
+    /// assert_eq!(U16Be::slice_to_bytes(buf.to_units()), b"\xD8\x3E\xDD\x80");
+    /// ```
+    U16Be(u16; 2) : from_be_bytes to_be_bytes, "\\u{:04x}", 2;
+
+    /// A little endian UTF32 [`Unit`], regardless of the native byte order.
+    ///
+    /// Unlike [`u32`], [`U32Le`] is stored as bytes, and has an alignment of `1`: it can be borrowed directly out of file contents.
+    U32Le(u32; 4) : from_le_bytes to_le_bytes, "\\u{{{:x}}}", 1;
+
+    /// A big endian UTF32 [`Unit`], regardless of the native byte order (e.g. big endian Mach-O, `UTF-32BE` protocol fields.)
+    ///
+    /// Unlike [`u32`], [`U32Be`] is stored as bytes, and has an alignment of `1`: it can be borrowed directly out of file contents.
+    U32Be(u32; 4) : from_be_bytes to_be_bytes, "\\u{{{:x}}}", 1;
+}
+
+
+
+#[test] fn endian() {
+    assert_eq!(U16Le::new(0x1234), U16Le::from(0x1234));
+    assert_eq!(U16Le::slice_to_bytes(&[U16Le::new(0x1234)]), b"\x34\x12");
+    assert_eq!(U16Be::slice_to_bytes(&[U16Be::new(0x1234)]), b"\x12\x34");
+    assert_eq!(U32Le::slice_to_bytes(&[U32Le::new(0x1234)]), b"\x34\x12\0\0");
+    assert_eq!(U32Be::slice_to_bytes(&[U32Be::new(0x1234)]), b"\0\0\x12\x34");
+    assert_eq!(u32::from(U32Be::new(0x1234)), 0x1234);
+    assert_eq!(U16Le::slice_from_bytes(b"abc"), None);
+    assert_eq!(U32Be::slice_from_bytes(b"\0\0\0a").map(|u| u[0].get()), Some(0x61));
+    assert_eq!(core::mem::align_of::<CStrBuf<U32Be, 4>>(), 1);
+
+    let crab_be = U16Be::slice_from_bytes(b"\xD8\x3E\xDD\x80\0a\xDC\0\0\0").unwrap();
+    assert_eq!(U16Be::decode(crab_be), (Some('\u{1F980}'), 2));
+    assert_eq!(U16Be::decode(&crab_be[..1]), (None, 1));
+    let crab_be = CStrNonNull::<U16Be>::from_units_with_nul(crab_be).unwrap();
+    assert!(crab_be.chars_lossy().eq(['\u{1F980}', 'a', '\u{FFFD}'].iter().copied()));
+    #[cfg(feature = "alloc")] assert_eq!(alloc::format!("{:?}", crab_be), "\"\u{1F980}a\\udc00\"");
+
+    let mut le = CStrBuf::<U16Le, 4>::default();
+    le.set_str_truncate("\u{1F980}").unwrap();
+    assert_eq!(U16Le::slice_to_bytes(le.to_units()), b"\x3E\xD8\x80\xDD");
+
+    let mut le = CStrBuf::<U32Le, 4>::default();
+    le.set_str_truncate("ab").unwrap();
+    assert_eq!(U32Le::slice_to_bytes(le.to_units()), b"a\0\0\0b\0\0\0");
+    assert_eq!(U32Le::decode(U32Le::slice_from_bytes(b"\0\xD8\0\0").unwrap()), (None, 1));
+    #[cfg(feature = "alloc")] assert_eq!(le.to_string_lossy(), "ab");
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("\u{1F980}", |s: CStrNonNull<U16Be>| U16Be::slice_to_bytes(s.to_units()).to_vec()).unwrap(), b"\xD8\x3E\xDD\x80");
+}
@@ -1,26 +1,33 @@
-#[cfg(feature = "std")] use std::error::Error;
-#[cfg(feature = "std")] use std::ffi;
+#[cfg(feature = "alloc")] use alloc::ffi::NulError;
 
-use core::fmt::{self, Debug, Display, Formatter};
+use core::convert::Infallible;
+use core::ffi::FromBytesWithNulError;
+#[cfg(    feature = "std" )] use std::error::Error;
+#[cfg(not(feature = "std"))] use core::error::Error; // Rust 1.81+
 
-#[cfg_attr(not(feature = "std"), allow(dead_code))]
-macro_rules! convert {
-    ( $src:ty => $dst:ty ) => {
-        impl From<$src> for $dst {
-            fn from(_: $src) -> Self { Self(()) }
-        }
-    };
-}
+use core::fmt::{self, Debug, Display, Formatter};
 
 
 
 /// The buffer in question is too small to contain the string in question
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct BufferTooSmallError(pub(crate) ());
+pub struct BufferTooSmallError {
+    required: usize,
+    capacity: usize,
+}
 
-impl Debug      for BufferTooSmallError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("BufferTooSmallError") } }
-impl Display    for BufferTooSmallError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("data provided is too large for the buffer") } }
-#[cfg(feature = "std")]
+impl BufferTooSmallError {
+    pub(crate) fn new(required: usize, capacity: usize) -> Self { Self { required, capacity } }
+
+    /// The number of units (including any terminal `\0`) the buffer would have needed to fit the data.
+    pub fn required(&self) -> usize { self.required }
+
+    /// The number of units the buffer actually had room for.
+    pub fn capacity(&self) -> usize { self.capacity }
+}
+
+impl Debug      for BufferTooSmallError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("BufferTooSmallError").field("required", &self.required).field("capacity", &self.capacity).finish() } }
+impl Display    for BufferTooSmallError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "data provided is too large for the buffer ({} units required, {} available)", self.required, self.capacity) } }
 impl Error      for BufferTooSmallError { fn description(&self) -> &str { "data provided is too large for the buffer" } }
 
 
@@ -31,30 +38,497 @@ pub struct NotNulTerminatedError(pub(crate) ());
 
 impl Debug      for NotNulTerminatedError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("NotNulTerminatedError") } }
 impl Display    for NotNulTerminatedError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("data provided is not nul terminated") } }
-#[cfg(feature = "std")]
 impl Error      for NotNulTerminatedError { fn description(&self) -> &str { "data provided is not nul terminated" } }
 
 
 
+/// A (possibly nested) field of a [`ValidateCStr`](crate::ValidateCStr) struct isn't `\0`-terminated.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldError {
+    path:       [FieldPathSegment; FieldError::MAX_DEPTH],
+    len:        u8,
+    truncated:  bool,
+}
+
+/// One segment of a [`FieldError::path`]: `.field` or `[index]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FieldPathSegment {
+    /// A struct field (or tuple struct index, e.g. `"0"`.)
+    Field(&'static str),
+    /// An array index.
+    Index(usize),
+}
+
+impl FieldError {
+    /// The maximum number of path segments tracked.  Deeper paths discard their innermost segments.
+    pub const MAX_DEPTH : usize = 5;
+
+    /// Prepend `.field` to the path.  Used by `#[derive(ValidateCStr)]`.
+    pub fn with_field(self, field: &'static str) -> Self { self.with(FieldPathSegment::Field(field)) }
+
+    /// Prepend `[index]` to the path.
+    pub fn with_index(self, index: usize) -> Self { self.with(FieldPathSegment::Index(index)) }
+
+    /// The path to the offending buffer, outermost segment first.
+    pub fn path(&self) -> &[FieldPathSegment] { &self.path[..usize::from(self.len)] }
+
+    /// `true` if the path was deeper than [`FieldError::MAX_DEPTH`] and had some of it's innermost segments discarded.
+    pub fn is_path_truncated(&self) -> bool { self.truncated }
+
+    fn with(mut self, segment: FieldPathSegment) -> Self {
+        let mut len = usize::from(self.len);
+        if len == Self::MAX_DEPTH {
+            len -= 1;
+            self.truncated = true;
+        }
+        self.path.copy_within(..len, 1);
+        self.path[0] = segment;
+        self.len = (len + 1) as u8;
+        self
+    }
+}
+
+impl From<NotNulTerminatedError> for FieldError {
+    fn from(_: NotNulTerminatedError) -> Self { Self { path: [FieldPathSegment::Index(0); Self::MAX_DEPTH], len: 0, truncated: false } }
+}
+
+impl Debug      for FieldError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "FieldError({})", self) } }
+impl Display    for FieldError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_str("`")?;
+        for (i, segment) in self.path().iter().enumerate() {
+            match segment {
+                FieldPathSegment::Field(f) if i == 0    => fmt.write_str(f)?,
+                FieldPathSegment::Field(f)              => write!(fmt, ".{}", f)?,
+                FieldPathSegment::Index(i)              => write!(fmt, "[{}]", i)?,
+            }
+        }
+        if self.truncated { fmt.write_str("...")?; }
+        fmt.write_str("` is not nul terminated")
+    }
+}
+impl Error      for FieldError { fn description(&self) -> &str { "field is not nul terminated" } }
+
+
+
+/// A field of an [`IntoFfi`](crate::IntoFfi) struct couldn't be converted to it's C representation.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IntoFfiError {
+    field:  &'static str,
+    kind:   IntoFfiErrorKind,
+}
+
+/// What exactly went wrong converting a field in [`IntoFfi::to_ffi`](crate::IntoFfi::to_ffi)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntoFfiErrorKind {
+    /// The string contains an interior `\0`, which would silently truncate it.
+    InteriorNul(InteriorNulError),
+    /// The string doesn't fit in the target [`CStrBuf`](crate::CStrBuf).  Use `#[abistr(truncate)]` to truncate instead.
+    BufferTooSmall(BufferTooSmallError),
+    /// The string contains a non-ASCII character, which an [`Ascii`](crate::Ascii) field can't represent.
+    NotAscii(NotAsciiError),
+}
+
+impl IntoFfiError {
+    pub(crate) fn new(field: &'static str, kind: IntoFfiErrorKind) -> Self { Self { field, kind } }
+
+    /// The name of the Rust field that couldn't be converted.
+    pub fn field(&self) -> &'static str { self.field }
+
+    /// What exactly went wrong.
+    pub fn kind(&self) -> IntoFfiErrorKind { self.kind }
+}
+
+impl From<InteriorNulError>     for IntoFfiErrorKind { fn from(err: InteriorNulError) -> Self { IntoFfiErrorKind::InteriorNul(err) } }
+impl From<BufferTooSmallError>  for IntoFfiErrorKind { fn from(err: BufferTooSmallError) -> Self { IntoFfiErrorKind::BufferTooSmall(err) } }
+impl From<SetStrError>          for IntoFfiErrorKind {
+    fn from(err: SetStrError) -> Self {
+        match err {
+            SetStrError::InteriorNul(err)       => IntoFfiErrorKind::InteriorNul(err),
+            SetStrError::BufferTooSmall(err)    => IntoFfiErrorKind::BufferTooSmall(err),
+            SetStrError::NotAscii(err)          => IntoFfiErrorKind::NotAscii(err),
+        }
+    }
+}
+
+impl Debug      for IntoFfiError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("IntoFfiError").field("field", &self.field).field("kind", &self.kind).finish() } }
+impl Display    for IntoFfiError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self.kind {
+            IntoFfiErrorKind::InteriorNul(err)      => write!(fmt, "unable to convert `{}` to C: {}", self.field, err),
+            IntoFfiErrorKind::BufferTooSmall(err)   => write!(fmt, "unable to convert `{}` to C: {}", self.field, err),
+            IntoFfiErrorKind::NotAscii(err)         => write!(fmt, "unable to convert `{}` to C: {}", self.field, err),
+        }
+    }
+}
+impl Error      for IntoFfiError { fn description(&self) -> &str { "unable to convert field to C" } }
+
+
+
+/// A field of a [`FromFfi`](crate::FromFfi) struct couldn't be converted from it's C representation.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FromFfiError {
+    field:  &'static str,
+    kind:   FromFfiErrorKind,
+}
+
+/// What exactly went wrong converting a field in [`FromFfi::from_ffi`](crate::FromFfi::from_ffi)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FromFfiErrorKind {
+    /// The [`CStrBuf`](crate::CStrBuf) wasn't `\0`-terminated (only reported for `#[abistr(get = "str")]` fields.)
+    NotNulTerminated(NotNulTerminatedError),
+    /// The string contained an invalid sequence starting at unit `position` (only reported for `#[abistr(get = "str")]` fields.)
+    InvalidUnicode {
+        /// The index of the first unit of the invalid sequence.
+        position: usize,
+    },
+}
+
+impl FromFfiError {
+    pub(crate) fn new(field: &'static str, kind: FromFfiErrorKind) -> Self { Self { field, kind } }
+
+    /// The name of the Rust field that couldn't be converted.
+    pub fn field(&self) -> &'static str { self.field }
+
+    /// What exactly went wrong.
+    pub fn kind(&self) -> FromFfiErrorKind { self.kind }
+}
+
+impl From<NotNulTerminatedError> for FromFfiErrorKind { fn from(err: NotNulTerminatedError) -> Self { FromFfiErrorKind::NotNulTerminated(err) } }
+
+impl Debug      for FromFfiError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("FromFfiError").field("field", &self.field).field("kind", &self.kind).finish() } }
+impl Display    for FromFfiError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self.kind {
+            FromFfiErrorKind::NotNulTerminated(err)         => write!(fmt, "unable to convert `{}` from C: {}", self.field, err),
+            FromFfiErrorKind::InvalidUnicode { position }   => write!(fmt, "unable to convert `{}` from C: invalid unicode at position {}", self.field, position),
+        }
+    }
+}
+impl Error      for FromFfiError { fn description(&self) -> &str { "unable to convert field from C" } }
+
+
+
 /// The string in question contains no terminal `\0`, or contains an interior `\0`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct FromUnitsWithNulError(pub(crate) ());
-impl Debug      for FromUnitsWithNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("FromUnitsWithNulError") } }
-impl Display    for FromUnitsWithNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("data provided is not nul terminated, or contains interior nuls") } }
-#[cfg(feature = "std")]
+pub struct FromUnitsWithNulError(FromUnitsWithNulErrorKind);
+
+/// What exactly was wrong with the data passed to e.g. [`CStrPtr::from_units_with_nul`](crate::CStrPtr::from_units_with_nul)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FromUnitsWithNulErrorKind {
+    /// The data provided contains no terminal `\0`.
+    NotNulTerminated,
+
+    /// The data provided contains an interior `\0` at `position` (in units, not bytes.)
+    InteriorNul {
+        /// The index of the first interior `\0`.
+        position: usize,
+    },
+
+    /// The data provided contains no terminal `\0`, or contains an interior `\0`.
+    ///
+    /// Only produced when converting from [`core::ffi::FromBytesWithNulError`], which doesn't say which.
+    Unknown,
+}
+
+impl FromUnitsWithNulError {
+    pub(crate) fn not_nul_terminated() -> Self { Self(FromUnitsWithNulErrorKind::NotNulTerminated) }
+    pub(crate) fn interior_nul(position: usize) -> Self { Self(FromUnitsWithNulErrorKind::InteriorNul { position }) }
+    pub(crate) fn unknown() -> Self { Self(FromUnitsWithNulErrorKind::Unknown) }
+
+    /// Distinguishes between missing terminal `\0`s and interior `\0`s.
+    pub fn kind(&self) -> FromUnitsWithNulErrorKind { self.0 }
+
+    /// The index of the first interior `\0`, if that's what caused this error.
+    pub fn nul_position(&self) -> Option<usize> {
+        match self.0 {
+            FromUnitsWithNulErrorKind::NotNulTerminated             => None,
+            FromUnitsWithNulErrorKind::InteriorNul { position }     => Some(position),
+            FromUnitsWithNulErrorKind::Unknown                      => None,
+        }
+    }
+}
+
+impl Debug      for FromUnitsWithNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_tuple("FromUnitsWithNulError").field(&self.0).finish() } }
+impl Display    for FromUnitsWithNulError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            FromUnitsWithNulErrorKind::NotNulTerminated             => fmt.write_str("data provided is not nul terminated"),
+            FromUnitsWithNulErrorKind::InteriorNul { position }     => write!(fmt, "data provided contains an interior nul at position {}", position),
+            FromUnitsWithNulErrorKind::Unknown                      => fmt.write_str("data provided is not nul terminated, or contains an interior nul"),
+        }
+    }
+}
 impl Error      for FromUnitsWithNulError { fn description(&self) -> &str { "data provided is not nul terminated, or contains interior nuls" } }
-#[cfg(feature = "std")] convert!(ffi::FromBytesWithNulError => FromUnitsWithNulError);
-// convert!(ffi::FromVecWithNulError => FromUnitsWithNulError); // not yet stable
-// convert!(NotNulTerminatedError => FromUnitsWithNulError);    // ...is this lossy?
-// convert!(InteriorNulError => FromUnitsWithNulError);         // ...is this lossy?
+
+impl From<NotNulTerminatedError> for FromUnitsWithNulError { fn from(_: NotNulTerminatedError) -> Self { Self::not_nul_terminated() } }
+impl From<InteriorNulError>      for FromUnitsWithNulError { fn from(err: InteriorNulError) -> Self { Self::interior_nul(err.position) } }
+impl From<Infallible>            for FromUnitsWithNulError { fn from(never: Infallible) -> Self { match never {} } }
+
+impl From<FromBytesWithNulError> for FromUnitsWithNulError { fn from(_: FromBytesWithNulError) -> Self { Self::unknown() } } // core doesn't expose the position of the interior nul
+// impl From<ffi::FromVecWithNulError> for FromUnitsWithNulError // not yet stable
 
 
 
 /// The string in question contains an interior `\0`.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct InteriorNulError(pub(crate) ());
-impl Debug      for InteriorNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("InteriorNulError") } }
-impl Display    for InteriorNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.write_str("data provided contains interior nuls") } }
-#[cfg(feature = "std")]
+pub struct InteriorNulError {
+    position: usize,
+}
+
+impl InteriorNulError {
+    pub(crate) fn new(position: usize) -> Self { Self { position } }
+
+    /// The index of the first interior `\0` (in units, not bytes.)
+    pub fn nul_position(&self) -> usize { self.position }
+}
+
+impl Debug      for InteriorNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("InteriorNulError").field("position", &self.position).finish() } }
+impl Display    for InteriorNulError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "data provided contains an interior nul at position {}", self.position) } }
 impl Error      for InteriorNulError { fn description(&self) -> &str { "data provided contains interior nuls" } }
-#[cfg(feature = "std")] convert!(ffi::NulError => InteriorNulError);
+#[cfg(feature = "alloc")] impl From<NulError> for InteriorNulError { fn from(err: NulError) -> Self { Self::new(err.nul_position()) } }
+impl From<Infallible> for InteriorNulError { fn from(never: Infallible) -> Self { match never {} } }
+
+
+
+/// What exactly went wrong setting a [`CStrBuf`](crate::CStrBuf) from a [`str`] (e.g. in [`CStrBuf::try_set_str`](crate::CStrBuf::try_set_str).)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SetStrError {
+    /// The string contains an interior `\0`, which would silently truncate it.
+    InteriorNul(InteriorNulError),
+    /// The encoded string (+ `\0`) doesn't fit in the buffer.
+    BufferTooSmall(BufferTooSmallError),
+    /// The string contains a non-ASCII character, which an [`Ascii`](crate::Ascii) buffer can't represent.
+    NotAscii(NotAsciiError),
+}
+
+impl From<InteriorNulError>     for SetStrError { fn from(err: InteriorNulError) -> Self { SetStrError::InteriorNul(err) } }
+impl From<BufferTooSmallError>  for SetStrError { fn from(err: BufferTooSmallError) -> Self { SetStrError::BufferTooSmall(err) } }
+impl From<NotAsciiError>        for SetStrError { fn from(err: NotAsciiError) -> Self { SetStrError::NotAscii(err) } }
+impl From<AsciiStrError>        for SetStrError {
+    fn from(err: AsciiStrError) -> Self {
+        match err {
+            AsciiStrError::InteriorNul(err) => SetStrError::InteriorNul(err),
+            AsciiStrError::NotAscii(err)    => SetStrError::NotAscii(err),
+        }
+    }
+}
+
+impl Display    for SetStrError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            SetStrError::InteriorNul(err)       => Display::fmt(err, fmt),
+            SetStrError::BufferTooSmall(err)    => Display::fmt(err, fmt),
+            SetStrError::NotAscii(err)          => Display::fmt(err, fmt),
+        }
+    }
+}
+impl Error      for SetStrError { fn description(&self) -> &str { "unable to set buffer" } }
+
+
+
+/// What exactly went wrong converting an [`OsStr`](std::ffi::OsStr) or [`Path`](std::path::Path) to a C string (e.g. via [`TryIntoAsCStr`](crate::TryIntoAsCStr).)
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OsStrError {
+    /// The string contains an interior `\0`.
+    InteriorNul(InteriorNulError),
+    /// The string isn't valid unicode, and can't be passed through as-is to the target unit on this platform (e.g. non-UTF8 bytes on unix, converted to UTF16.)
+    NotUnicode,
+}
+
+#[cfg(feature = "std")] impl From<InteriorNulError> for OsStrError { fn from(err: InteriorNulError) -> Self { OsStrError::InteriorNul(err) } }
+
+#[cfg(feature = "std")] impl Display for OsStrError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            OsStrError::InteriorNul(err)    => Display::fmt(err, fmt),
+            OsStrError::NotUnicode          => fmt.write_str("data provided is not valid unicode"),
+        }
+    }
+}
+#[cfg(feature = "std")] impl Error for OsStrError { fn description(&self) -> &str { "unable to convert OS string" } }
+
+
+
+/// The string in question contains a non-ASCII byte (`0x80 ..= 0xFF`.)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NotAsciiError {
+    position: usize,
+}
+
+impl NotAsciiError {
+    pub(crate) fn new(position: usize) -> Self { Self { position } }
+
+    /// The index of the first non-ASCII byte.
+    pub fn position(&self) -> usize { self.position }
+}
+
+impl Debug      for NotAsciiError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("NotAsciiError").field("position", &self.position).finish() } }
+impl Display    for NotAsciiError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "data provided contains a non-ASCII byte at position {}", self.position) } }
+impl Error      for NotAsciiError { fn description(&self) -> &str { "data provided contains non-ASCII bytes" } }
+
+
+
+/// What exactly went wrong encoding a [`str`] as [`Ascii`](crate::Ascii) (e.g. via [`SmallCString::new`](crate::SmallCString::new), or [`TryIntoAsCStr`](crate::TryIntoAsCStr).)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AsciiStrError {
+    /// The string contains an interior `\0`.
+    InteriorNul(InteriorNulError),
+    /// The string contains a non-ASCII character.
+    NotAscii(NotAsciiError),
+}
+
+impl From<InteriorNulError> for AsciiStrError { fn from(err: InteriorNulError) -> Self { AsciiStrError::InteriorNul(err) } }
+impl From<NotAsciiError>    for AsciiStrError { fn from(err: NotAsciiError) -> Self { AsciiStrError::NotAscii(err) } }
+
+impl Display    for AsciiStrError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            AsciiStrError::InteriorNul(err) => Display::fmt(err, fmt),
+            AsciiStrError::NotAscii(err)    => Display::fmt(err, fmt),
+        }
+    }
+}
+impl Error      for AsciiStrError { fn description(&self) -> &str { "unable to encode string as ASCII" } }
+
+
+
+/// The string in question isn't valid UTF16 (it contains an unpaired surrogate.)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Utf16Error {
+    valid_up_to: usize,
+}
+
+impl Utf16Error {
+    pub(crate) fn new(valid_up_to: usize) -> Self { Self { valid_up_to } }
+
+    /// The number of leading units which were valid UTF16 (e.g. the index of the first unpaired surrogate.)
+    pub fn valid_up_to(&self) -> usize { self.valid_up_to }
+}
+
+impl Debug      for Utf16Error { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("Utf16Error").field("valid_up_to", &self.valid_up_to).finish() } }
+impl Display    for Utf16Error { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "data provided contains an unpaired surrogate at position {}", self.valid_up_to) } }
+impl Error      for Utf16Error { fn description(&self) -> &str { "data provided is not valid UTF16" } }
+
+
+
+/// The string in question contains a character which can't be represented in the target [`CodePage`](crate::CodePage).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnmappableCharError {
+    ch:         char,
+    position:   usize,
+}
+
+impl UnmappableCharError {
+    pub(crate) fn new(ch: char, position: usize) -> Self { Self { ch, position } }
+
+    /// The character which couldn't be encoded.
+    pub fn char(&self) -> char { self.ch }
+
+    /// The byte index of [`char`](Self::char) in the source [`str`].
+    pub fn position(&self) -> usize { self.position }
+}
+
+impl Debug      for UnmappableCharError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("UnmappableCharError").field("ch", &self.ch).field("position", &self.position).finish() } }
+impl Display    for UnmappableCharError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "{:?} at position {} can't be represented in the target code page", self.ch, self.position) } }
+impl Error      for UnmappableCharError { fn description(&self) -> &str { "character can't be represented in the target code page" } }
+
+
+
+/// What exactly went wrong encoding a string with a [`CodePage`](crate::CodePage) (e.g. in [`CStrBuf::set_with`](crate::CStrBuf::set_with).)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EncodeError {
+    /// The string contains a character the code page can't represent.
+    Unmappable(UnmappableCharError),
+    /// The encoded string (+ `\0`) doesn't fit in the buffer.
+    BufferTooSmall(BufferTooSmallError),
+    /// The string contains an interior `\0` (at a byte index in the source [`str`]), which would silently truncate it.
+    /// Only [`CodePage::Mutf8`](crate::CodePage::Mutf8) can represent `'\0'` without terminating the string.
+    InteriorNul(InteriorNulError),
+}
+
+impl From<UnmappableCharError> for EncodeError { fn from(err: UnmappableCharError) -> Self { EncodeError::Unmappable(err) } }
+impl From<BufferTooSmallError> for EncodeError { fn from(err: BufferTooSmallError) -> Self { EncodeError::BufferTooSmall(err) } }
+impl From<InteriorNulError>    for EncodeError { fn from(err: InteriorNulError)    -> Self { EncodeError::InteriorNul(err) } }
+
+impl Display    for EncodeError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            EncodeError::Unmappable(err)        => Display::fmt(err, fmt),
+            EncodeError::BufferTooSmall(err)    => Display::fmt(err, fmt),
+            EncodeError::InteriorNul(err)       => Display::fmt(err, fmt),
+        }
+    }
+}
+impl Error      for EncodeError { fn description(&self) -> &str { "unable to encode string" } }
+
+
+
+/// An argument to [`ccall!`] couldn't be converted to a C string.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CallArgError {
+    index:  usize,
+    arg:    &'static str,
+    error:  FromUnitsWithNulError,
+}
+
+impl CallArgError {
+    pub(crate) fn new(index: usize, arg: &'static str, error: FromUnitsWithNulError) -> Self { Self { index, arg, error } }
+
+    /// The (0-based) index of the argument that couldn't be converted.
+    pub fn index(&self) -> usize { self.index }
+
+    /// The source text of the argument that couldn't be converted.
+    pub fn arg(&self) -> &'static str { self.arg }
+
+    /// The underlying conversion error: an interior `\0` (e.g. in a [`str`]), or a missing `\0` (e.g. in a [`CStrBuf`](crate::CStrBuf).)
+    pub fn error(&self) -> FromUnitsWithNulError { self.error }
+}
+
+impl From<CallArgError> for FromUnitsWithNulError { fn from(err: CallArgError) -> Self { err.error } }
+
+impl Debug      for CallArgError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { fmt.debug_struct("CallArgError").field("index", &self.index).field("arg", &self.arg).field("error", &self.error).finish() } }
+impl Display    for CallArgError { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "invalid argument {} (`{}`): {}", self.index, self.arg, self.error) } }
+impl Error      for CallArgError {
+    fn description(&self) -> &str { "argument is not a valid C string" }
+    fn source(&self) -> Option<&(dyn Error + 'static)> { Some(&self.error) }
+}
+
+
+#[cfg(feature = "std")]
+#[test] fn conversions() {
+    let err = crate::CStrPtr::<u8>::from_units_with_nul(b"abc\0def\0").unwrap_err();
+    assert_eq!(err.kind(), FromUnitsWithNulErrorKind::InteriorNul { position: 3 });
+    assert_eq!(err.nul_position(), Some(3));
+    assert_eq!(err.to_string(), "data provided contains an interior nul at position 3");
+
+    let err = crate::CStrPtr::<u8>::from_units_with_nul(b"abc").unwrap_err();
+    assert_eq!(err.kind(), FromUnitsWithNulErrorKind::NotNulTerminated);
+    assert_eq!(err.nul_position(), None);
+
+    let err = InteriorNulError::from(std::ffi::CString::new("ab\0c").unwrap_err());
+    assert_eq!(err.nul_position(), 2);
+    assert_eq!(FromUnitsWithNulError::from(err), FromUnitsWithNulError::interior_nul(2));
+    assert_eq!(FromUnitsWithNulError::from(NotNulTerminatedError(())), FromUnitsWithNulError::not_nul_terminated());
+
+    let err = FromUnitsWithNulError::from(core::ffi::CStr::from_bytes_with_nul(b"ab\0c\0").unwrap_err());
+    assert_eq!(err.kind(), FromUnitsWithNulErrorKind::Unknown);
+    assert_eq!(err.nul_position(), None);
+    assert_eq!(err.to_string(), "data provided is not nul terminated, or contains an interior nul");
+
+    let err = FieldError::from(NotNulTerminatedError(())).with_index(3).with_field("names").with_field("inner");
+    assert_eq!(err.path(), &[FieldPathSegment::Field("inner"), FieldPathSegment::Field("names"), FieldPathSegment::Index(3)]);
+    assert_eq!(err.to_string(), "`inner.names[3]` is not nul terminated");
+    let err = (0 .. 10).fold(FieldError::from(NotNulTerminatedError(())), |err, i| err.with_index(i));
+    assert_eq!(err.path().len(), FieldError::MAX_DEPTH);
+    assert_eq!(err.to_string(), "`[9][8][7][6][5]...` is not nul terminated");
+
+    let err = CallArgError::new(1, "name", FromUnitsWithNulError::interior_nul(2));
+    assert_eq!(err.source().and_then(|e| e.downcast_ref()), Some(&FromUnitsWithNulError::interior_nul(2)));
+    assert!(err < CallArgError::new(2, "name", FromUnitsWithNulError::not_nul_terminated()));
+
+    let err = BufferTooSmallError::new(11, 8);
+    assert_eq!(format!("{:?}", err), "BufferTooSmallError { required: 11, capacity: 8 }");
+    assert_eq!(err.to_string(), "data provided is too large for the buffer (11 units required, 8 available)");
+}
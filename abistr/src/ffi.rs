@@ -0,0 +1,280 @@
+use crate::*;
+use crate::unit::decode_lossy;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::any::Any;
+
+
+
+/// Convert a Rust struct into it's C-ABI counterpart.  Typically implemented via <code>#[derive([IntoFfi](macro@IntoFfi))]</code>.
+pub trait IntoFfi {
+    /// The C-ABI struct, borrowing any strings for `'s`.
+    type Ffi<'s>;
+
+    /// Convert `self` into [`Self::Ffi`], with any strings it points to kept alive by the returned [`FfiGuard`].
+    fn to_ffi(&self) -> Result<FfiGuard<Self>, IntoFfiError>;
+
+    /// Shorten the lifetime of `raw`.  Should be implemented as simply `{ raw }`, which only compiles if [`Self::Ffi`] is covariant over `'s`.
+    fn shorten<'a>(raw: &'a Self::Ffi<'static>) -> &'a Self::Ffi<'a>;
+}
+
+/// Convert a C-ABI struct into it's Rust counterpart.  Typically implemented via <code>#[derive([FromFfi](macro@FromFfi))]</code>.
+pub trait FromFfi : Sized {
+    /// The C-ABI struct, borrowing any strings for `'s`.
+    type Ffi<'s>;
+
+    /// Copy `raw` into a new `Self`.
+    fn from_ffi(raw: &Self::Ffi<'_>) -> Result<Self, FromFfiError>;
+}
+
+/// A C-ABI struct, and the storage backing any strings it points to.  Returned by [`IntoFfi::to_ffi`].
+pub struct FfiGuard<T: IntoFfi + ?Sized> {
+    raw:        T::Ffi<'static>, // must be declared (and thus dropped) before `storage`
+    #[allow(dead_code)] // only read through `raw`
+    storage:    FfiStorage,
+}
+
+impl<T: IntoFfi + ?Sized> FfiGuard<T> {
+    pub(crate) fn new(raw: T::Ffi<'static>, storage: FfiStorage) -> Self { Self { raw, storage } }
+
+    /// Borrow the C-ABI struct.  Any strings it points to remain valid for as long as `self` is borrowed.
+    pub fn get(&self) -> &T::Ffi<'_> { T::shorten(&self.raw) }
+
+    /// Treat `self` as a raw pointer to the C-ABI struct, e.g. for passing to C functions.
+    pub fn as_ptr(&self) -> *const T::Ffi<'_> { self.get() }
+}
+
+impl<T: IntoFfi + ?Sized> core::fmt::Debug for FfiGuard<T> where for<'s> T::Ffi<'s> : core::fmt::Debug {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result { fmt.debug_tuple("FfiGuard").field(self.get()).finish() }
+}
+
+
+
+/// Owns the strings an [`IntoFfi`] struct points to.  Not part of the public API.
+#[derive(Default)]
+pub struct FfiStorage(Vec<Box<dyn Any>>);
+
+impl FfiStorage {
+    /// Encode `s` as a `\0`-terminated heap allocation owned by `self`.
+    fn push_str<U: Unit>(&mut self, s: &str) -> Result<*const U::CChar, IntoFfiErrorKind> {
+        let units = encode::<U>(s)?;
+        let ptr = units.as_ptr().cast();
+        self.0.push(Box::new(units)); // moves the `Box`, not the heap allocation `ptr` points into
+        Ok(ptr)
+    }
+}
+
+/// Encode `s` as `\0`-terminated units.
+fn encode<U: Unit>(s: &str) -> Result<Box<[U]>, IntoFfiErrorKind> {
+    let mut units = Vec::with_capacity(s.len() + 1);
+    let mut buf = [U::NUL; 4];
+    for ch in s.chars() {
+        if ch == '\0' { return Err(InteriorNulError::new(units.len()).into()) }
+        U::check_char(ch, units.len()).map_err(|err| IntoFfiErrorKind::from(err.into()))?;
+        units.extend_from_slice(U::encode_char(ch, &mut buf));
+    }
+    units.push(U::NUL);
+    Ok(units.into_boxed_slice())
+}
+
+/// Decode `units` to a [`String`], either lossily or strictly.
+fn decode<U: Unit>(units: &[U], strict: bool) -> Result<String, FromFfiErrorKind> {
+    if !strict { return Ok(decode_lossy(units).collect()) }
+    let mut s = String::with_capacity(units.len());
+    let mut rest = units;
+    while !rest.is_empty() {
+        let (ch, n) = U::decode(rest);
+        match ch {
+            Some(ch)    => s.push(ch),
+            None        => return Err(FromFfiErrorKind::InvalidUnicode { position: units.len() - rest.len() }),
+        }
+        rest = &rest[n..];
+    }
+    Ok(s)
+}
+
+
+
+/// A field of a `#[derive(IntoFfi)]` struct, convertible to `T`.  Not part of the public API.
+pub trait IntoFfiField<T> {
+    /// ### Safety
+    /// *   The returned value may borrow from `storage` with an unbounded lifetime, and must not outlive it.
+    unsafe fn to_ffi_field(&self, storage: &mut FfiStorage, truncate: bool) -> Result<T, IntoFfiErrorKind>;
+}
+
+impl<T: Clone> IntoFfiField<T> for T {
+    unsafe fn to_ffi_field(&self, _storage: &mut FfiStorage, _truncate: bool) -> Result<T, IntoFfiErrorKind> { Ok(self.clone()) }
+}
+
+impl<U: Unit> IntoFfiField<CStrPtr<'static, U>> for String {
+    unsafe fn to_ffi_field(&self, storage: &mut FfiStorage, _truncate: bool) -> Result<CStrPtr<'static, U>, IntoFfiErrorKind> {
+        Ok(CStrPtr::from_ptr_unbounded(storage.push_str::<U>(self)?))
+    }
+}
+
+impl<U: Unit> IntoFfiField<CStrNonNull<'static, U>> for String {
+    unsafe fn to_ffi_field(&self, storage: &mut FfiStorage, _truncate: bool) -> Result<CStrNonNull<'static, U>, IntoFfiErrorKind> {
+        Ok(CStrNonNull::from_ptr_unchecked_unbounded(storage.push_str::<U>(self)?))
+    }
+}
+
+impl<U: Unit> IntoFfiField<CStrPtr<'static, U>> for Option<String> {
+    unsafe fn to_ffi_field(&self, storage: &mut FfiStorage, truncate: bool) -> Result<CStrPtr<'static, U>, IntoFfiErrorKind> {
+        match self {
+            None    => Ok(CStrPtr::NULL),
+            Some(s) => s.to_ffi_field(storage, truncate),
+        }
+    }
+}
+
+impl<U: Unit> IntoFfiField<Option<CStrNonNull<'static, U>>> for Option<String> {
+    unsafe fn to_ffi_field(&self, storage: &mut FfiStorage, truncate: bool) -> Result<Option<CStrNonNull<'static, U>>, IntoFfiErrorKind> {
+        match self {
+            None    => Ok(None),
+            Some(s) => s.to_ffi_field(storage, truncate).map(Some),
+        }
+    }
+}
+
+impl<U: Unit, const N: usize> IntoFfiField<CStrBuf<U, N>> for String {
+    unsafe fn to_ffi_field(&self, _storage: &mut FfiStorage, truncate: bool) -> Result<CStrBuf<U, N>, IntoFfiErrorKind> {
+        if let Some(nul) = self.find('\0') { return Err(InteriorNulError::new(encode::<U>(&self[..nul])?.len() - 1).into()) }
+        let mut buf = CStrBuf::default();
+        if truncate {
+            let _ = buf.set_str_truncate(self);
+        } else {
+            buf.try_set_str(self)?;
+        }
+        Ok(buf)
+    }
+}
+
+
+
+/// A field of a `#[derive(FromFfi)]` struct, convertible from `T`.  Not part of the public API.
+pub trait FromFfiField<T> : Sized {
+    /// Convert `raw`, failing on invalid unicode if `strict`.
+    fn from_ffi_field(raw: &T, strict: bool) -> Result<Self, FromFfiErrorKind>;
+}
+
+impl<T: Clone> FromFfiField<T> for T {
+    fn from_ffi_field(raw: &T, _strict: bool) -> Result<Self, FromFfiErrorKind> { Ok(raw.clone()) }
+}
+
+impl<U: Unit> FromFfiField<CStrPtr<'_, U>> for String {
+    fn from_ffi_field(raw: &CStrPtr<'_, U>, strict: bool) -> Result<Self, FromFfiErrorKind> { decode(raw.to_units(), strict) }
+}
+
+impl<U: Unit> FromFfiField<CStrNonNull<'_, U>> for String {
+    fn from_ffi_field(raw: &CStrNonNull<'_, U>, strict: bool) -> Result<Self, FromFfiErrorKind> { decode(raw.to_units(), strict) }
+}
+
+impl<U: Unit, const N: usize> FromFfiField<CStrBuf<U, N>> for String {
+    fn from_ffi_field(raw: &CStrBuf<U, N>, strict: bool) -> Result<Self, FromFfiErrorKind> {
+        if strict { raw.validate()?; }
+        decode(raw.to_units(), strict)
+    }
+}
+
+impl<U: Unit> FromFfiField<CStrPtr<'_, U>> for Option<String> {
+    fn from_ffi_field(raw: &CStrPtr<'_, U>, strict: bool) -> Result<Self, FromFfiErrorKind> {
+        if raw.is_null() { Ok(None) } else { decode(raw.to_units(), strict).map(Some) }
+    }
+}
+
+impl<U: Unit> FromFfiField<Option<CStrNonNull<'_, U>>> for Option<String> {
+    fn from_ffi_field(raw: &Option<CStrNonNull<'_, U>>, strict: bool) -> Result<Self, FromFfiErrorKind> {
+        raw.as_ref().map(|raw| decode(raw.to_units(), strict)).transpose()
+    }
+}
+
+
+#[cfg(feature = "std")]
+#[test] fn round_trip() {
+    #[derive(Debug, Default)]
+    #[repr(C)] struct RawDesc<'s> {
+        name:   CStrPtr<'s>,
+        wide:   CStrNonNull<'s, u16>,
+        label:  CStrBuf<u8, 4>,
+        alias:  Option<CStrNonNull<'s>>,
+        flags:  u32,
+        extra:  u32,
+    }
+
+    #[derive(IntoFfi, FromFfi, Debug, Default, PartialEq)]
+    #[abistr(ffi = "RawDesc<'s>", default)]
+    struct Desc {
+        name:                                   String,
+        wide:                                   String,
+        #[abistr(truncate)]                     label:  String,
+        #[abistr(rename = "alias", get = "str")] nick:  Option<String>,
+        flags:                                  u32,
+        #[abistr(skip)]                         cache:  Vec<u8>,
+    }
+
+    let desc = Desc { name: "name".into(), wide: "wide".into(), label: "too long".into(), nick: None, flags: 42, cache: vec![1] };
+    let guard = desc.to_ffi().unwrap();
+    let raw = guard.get();
+    assert_eq!(raw.name.to_string_lossy(), "name");
+    assert_eq!(raw.wide.to_string_lossy(), "wide");
+    assert_eq!(raw.label.to_string_lossy(), "too");
+    assert!(raw.alias.is_none());
+    assert_eq!(raw.flags, 42);
+    assert_eq!(Desc::from_ffi(raw), Ok(Desc { label: "too".into(), cache: Vec::new(), ..desc }));
+
+    let err = Desc { name: "a\0b".into(), ..Desc::default() }.to_ffi().unwrap_err();
+    assert_eq!((err.field(), err.kind()), ("name", IntoFfiErrorKind::InteriorNul(InteriorNulError::new(1))));
+    assert_eq!(err.to_string(), "unable to convert `name` to C: data provided contains an interior nul at position 1");
+
+    let bad = [b'a', 0xFF, 0];
+    let raw = RawDesc { alias: CStrNonNull::from_units_with_nul(&bad).ok(), ..RawDesc::default() };
+    assert_eq!(Desc::from_ffi(&raw).map_err(|err| (err.field(), err.kind())), Err(("nick", FromFfiErrorKind::InvalidUnicode { position: 1 })));
+    let raw = RawDesc { name: CStrPtr::from_units_with_nul(&bad).unwrap(), ..RawDesc::default() };
+    assert_eq!(Desc::from_ffi(&raw).unwrap().name, "a\u{FFFD}");
+
+    #[derive(IntoFfi, Debug)]
+    #[abistr(ffi = "RawBuf", crate = "crate")]
+    struct Buf { label: String }
+    #[derive(Debug)] struct RawBuf { label: CStrBuf<u16, 4> }
+    assert_eq!(Buf { label: "abc".into() }.to_ffi().unwrap().get().label.to_string_lossy(), "abc");
+    let err = Buf { label: "abcd".into() }.to_ffi().unwrap_err();
+    assert_eq!(err.kind(), IntoFfiErrorKind::BufferTooSmall(BufferTooSmallError::new(5, 4)));
+
+    #[derive(IntoFfi, Debug)]
+    #[abistr(ffi = "RawAscii<'s>", crate = "crate")]
+    struct Ascii7 { name: String, #[abistr(truncate)] label: String }
+    #[derive(Debug)] struct RawAscii<'s> { name: CStrNonNull<'s, Ascii>, label: CStrBuf<Ascii, 8> }
+    let guard = Ascii7 { name: "abc".into(), label: "caf\u{E9}".into() }.to_ffi().unwrap();
+    assert_eq!((guard.get().name.as_str(), guard.get().label.as_cstr_non_null().unwrap().as_str()), ("abc", "caf?"));
+    let err = Ascii7 { name: "caf\u{E9}".into(), label: String::new() }.to_ffi().unwrap_err();
+    assert_eq!((err.field(), err.kind()), ("name", IntoFfiErrorKind::NotAscii(NotAsciiError::new(3))));
+}
+
+#[cfg(feature = "std")] #[allow(dead_code)] mod compile_tests {
+    /// ```compile_fail
+    /// use abistr::*;
+    /// #[derive(IntoFfi)] struct S { name: String }
+    /// ```
+    struct MissingFfi;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// struct Raw<'s> { name: CStrPtr<'s> }
+    /// #[derive(IntoFfi)] #[abistr(ffi = "Raw<'s>")] struct S(String);
+    /// ```
+    struct Tuple;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// struct Raw<'s> { name: CStrPtr<'s> }
+    /// #[derive(IntoFfi)] #[abistr(ffi = "Raw<'s>")] struct S { name: String }
+    /// let guard = S { name: "a".into() }.to_ffi().unwrap();
+    /// let name = guard.get().name;
+    /// drop(guard);
+    /// name.to_string_lossy();
+    /// ```
+    struct Dangling;
+}
@@ -1,62 +1,113 @@
-use core::fmt::{self, Formatter};
-
-pub(crate) fn cstr_bytes(bytes: &[u8], f: &mut Formatter) -> fmt::Result {
-    write!(f, "\"")?;
-    for b in bytes.iter().copied() {
-        match b {
-            b'\0'           => write!(f, "\\0")?,
-            b'\t'           => write!(f, "\\t")?,
-            b'\r'           => write!(f, "\\r")?,
-            b'\n'           => write!(f, "\\n")?,
-            b'\''           => write!(f, "\\'")?,
-            b'\"'           => write!(f, "\\\"")?,
-            b'\\'           => write!(f, "\\\\")?,
-            0x20 ..= 0x7E   => write!(f, "{}", b as char)?,
-            esc             => write!(f, "\\x{:02x}", esc)?,
-        }
+use crate::*;
+
+use core::fmt::{self, Debug, Display, Formatter, Write};
+
+
+
+/// Helper struct for printing C strings with [`format!`] and `{}`, returned by e.g. [`CStrPtr::display`].
+///
+/// Invalid sequences are replaced with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+/// Unlike <code>[to_string_lossy](CStrPtr::to_string_lossy)</code>, this never allocates.  Width, fill, alignment, and precision (in [`char`]s) are honored like [`str`]'s.
+#[derive(Clone, Copy)]
+pub struct CStrDisplay<'s, U: Unit> {
+    units: &'s [U],
+}
+
+impl<'s, U: Unit> CStrDisplay<'s, U> {
+    pub(crate) fn new(units: &'s [U]) -> Self { Self { units } }
+}
+
+impl<U: Unit> Debug for CStrDisplay<'_, U> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { U::debug(self.units, f) }
+}
+
+impl<U: Unit> Display for CStrDisplay<'_, U> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Like `str`, honor precision (truncating to that many `char`s) and width (padding with `f.fill()`, left aligned by default.)
+        let chars = decode_lossy(self.units).take(f.precision().unwrap_or(usize::MAX));
+        let pad = match f.width() {
+            None        => 0,
+            Some(width) => width.saturating_sub(chars.clone().count()),
+        };
+        let (before, after) = match f.align() {
+            None | Some(fmt::Alignment::Left)   => (0, pad),
+            Some(fmt::Alignment::Right)         => (pad, 0),
+            Some(fmt::Alignment::Center)        => (pad / 2, pad - pad / 2),
+        };
+        let fill = f.fill();
+        for _ in 0 .. before { f.write_char(fill)?; }
+        for ch in chars { f.write_char(ch)?; }
+        for _ in 0 .. after { f.write_char(fill)?; }
+        Ok(())
     }
-    write!(f, "\"")?;
-    Ok(())
-}
-
-pub(crate) fn c16_units(units: &[u16], f: &mut Formatter) -> fmt::Result {
-    write!(f, "\"")?;
-    for u in units.iter().copied() {
-        match u {
-            0x0000          => write!(f, "\\0")?,
-            0x0009          => write!(f, "\\t")?,
-            0x000D          => write!(f, "\\r")?,
-            0x000A          => write!(f, "\\n")?,
-            0x0027          => write!(f, "\\'")?,
-            0x0022          => write!(f, "\\\"")?,
-            0x005C          => write!(f, "\\\\")?,
-            0x20 ..= 0x7E   => write!(f, "{}", u as u8 as char)?,
-
-            // Rust doesn't have a UTF16 code unit escape.  Use a C++ style "\u1234" instead of a Rust style "\u{1234}"
-            // to underscore this fact, and discourage using this in text which might need to round trip, which would
-            // fail on surrogate pairs.
-            esc             => write!(f, "\\u{:04x}", esc)?,
+}
+
+
+
+
+/// Helper struct for hex-dumping the entire contents of a [`CStrBuf`] - including anything after the first `\0` - returned by [`CStrBuf::debug_buffer`].
+#[derive(Clone, Copy)]
+pub struct CStrBufDebug<'b, U: Unit> {
+    buffer: &'b [U],
+}
+
+impl<'b, U: Unit> CStrBufDebug<'b, U> {
+    pub(crate) fn new(buffer: &'b [U]) -> Self { Self { buffer } }
+}
+
+impl<U: Unit> Debug for CStrBufDebug<'_, U> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let nul = self.buffer.iter().position(|u| *u == U::NUL).unwrap_or(self.buffer.len());
+        U::debug(&self.buffer[..nul], f)?;
+        f.write_str(" [")?;
+        for (i, u) in self.buffer.iter().enumerate() {
+            if i != 0 { f.write_char(' ')?; }
+            write!(f, "{:01$x}", u, 2 * core::mem::size_of::<U>())?;
         }
+        f.write_char(']')
     }
-    write!(f, "\"")?;
-    Ok(())
-}
-
-pub(crate) fn c32_units(units: &[u32], f: &mut Formatter) -> fmt::Result {
-    write!(f, "\"")?;
-    for u in units.iter().copied() {
-        match u {
-            0x00000000      => write!(f, "\\0")?,
-            0x00000009      => write!(f, "\\t")?,
-            0x0000000D      => write!(f, "\\r")?,
-            0x0000000A      => write!(f, "\\n")?,
-            0x00000027      => write!(f, "\\'")?,
-            0x00000022      => write!(f, "\\\"")?,
-            0x0000005C      => write!(f, "\\\\")?,
-            0x20 ..= 0x7E   => write!(f, "{}", u as u8 as char)?,
-            esc             => write!(f, "\\u{{{:x}}}", esc)?,
-        }
+}
+
+
+
+/// Write `units` as a quoted string, printing valid characters as-is (escaping only quotes, control characters, etc.)
+/// and escaping each unit of invalid sequences with [`Unit::debug_unit`].
+pub(crate) fn cstr_units<U: Unit>(mut units: &[U], f: &mut Formatter) -> fmt::Result {
+    f.write_char('"')?;
+    let mut first = true;
+    while !units.is_empty() {
+        let (ch, n) = U::decode(units);
+        escape_decoded(ch, &units[..n], first, f)?;
+        units = &units[n..];
+        first = false;
+    }
+    f.write_char('"')
+}
+
+/// Write `ch` per [`escape_char`], or if it failed to decode, escape each of the `units` it was decoded from with [`Unit::debug_unit`].
+pub(crate) fn escape_decoded<U: Unit>(ch: Option<char>, units: &[U], first: bool, f: &mut Formatter) -> fmt::Result {
+    match ch {
+        Some(ch)    => escape_char(ch, first, f),
+        None        => units.iter().try_for_each(|u| U::debug_unit(*u, f)),
+    }
+}
+
+pub(crate) fn escape_char(ch: char, first: bool, f: &mut Formatter) -> fmt::Result {
+    match ch {
+        '\0'                => f.write_str("\\0"),
+        '\x20' ..= '\x7E'   => match ch {
+            '\'' | '\"' | '\\'  => write!(f, "\\{}", ch),
+            ch                  => f.write_char(ch),
+        },
+        ch if first         => write!(f, "{}", ch.escape_debug()),
+        ch => {
+            // Like `{:?}` for `str`, only escape grapheme extenders (combining accents etc.) at the start of the
+            // string.  `char::escape_debug` always escapes them, so borrow `str::escape_debug`'s logic instead.
+            let mut buf = [b'a'; 5];
+            let s = ch.encode_utf8(&mut buf[1..]).len() + 1;
+            let s = core::str::from_utf8(&buf[..s]).unwrap();
+            for e in s.escape_debug().skip(1) { f.write_char(e)?; }
+            Ok(())
+        },
     }
-    write!(f, "\"")?;
-    Ok(())
 }
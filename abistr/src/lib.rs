@@ -66,24 +66,133 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+#[cfg(feature = "alloc")] extern crate alloc;
+
 #[doc(hidden)] pub extern crate abistr_macros;
+extern crate self as abistr; // for derives (and doc links)
 
-#[cfg(doc)] use crate as abistr;
 #[cfg(doc)] use core::ptr::*;
 
 #[macro_use] mod macros;
 
+mod any_cstr;                           pub use any_cstr::*;
 mod as_traits;                          pub use as_traits::*;
 mod buffers;                            pub use buffers::*;
+mod code_page;                          pub use code_page::*;
+mod cstr_like;                          pub use cstr_like::*;
+mod cutf;                               pub use cutf::*;
+mod endian;                             pub use endian::*;
+mod encoding;                           pub use encoding::*;
 mod errors;                             pub use errors::*;
-mod fmt;
+#[cfg(feature = "alloc")] mod ffi;      #[cfg(feature = "alloc")] pub use ffi::{IntoFfi, FromFfi, FfiGuard};
+mod fmt;                                pub use fmt::*;
+mod nul_policy;                         pub use nul_policy::*;
 mod pointers;                           pub use pointers::*;
+#[cfg(feature = "alloc")] mod small_cstring; #[cfg(feature = "alloc")] pub use small_cstring::*;
 mod try_into_as_traits;                 pub use try_into_as_traits::*;
+mod unaligned;                          pub use unaligned::*;
 mod unit;                               pub use unit::*;
+mod validate;                           pub use validate::*;
+#[cfg(feature = "alloc")] mod wtf8;     #[cfg(feature = "alloc")] pub use wtf8::*;
 
-pub(crate) mod private {
-    pub use crate::unit::private::*;
+mod derive;
+#[doc(hidden)] pub mod __derive {
+    pub use crate::derive::*;
 }
 
-#[cfg(    feature = "std" )] use std::os::raw::c_char;
-#[cfg(not(feature = "std"))] type c_char = i8;
+/// Generate string getters/setters for a struct's C string fields.
+///
+/// Fields of type [`CStrBuf`], [`CStrPtr`], [`CStrNonNull`], or <code>[Option]<[CStrNonNull]></code> can be annotated with:
+///
+/// | Attribute                             | Generates |
+/// | ------------------------------------- | --------- |
+/// | `#[abistr(get)]`                      | `fn field(&self) -> Cow<str>` (lossy, requires the `alloc` feature)
+/// | `#[abistr(get = "str")]`              | `fn field(&self) -> Result<&str, Utf8Error>` (`u8` units only)
+/// | `#[abistr(get = "display")]`          | `fn field(&self) -> CStrDisplay<U>` (never allocates)
+/// | `#[abistr(set)]`                      | `fn set_field(&mut self, &str) -> Result<(), SetStrError>` via [`CStrBuf::set_str_truncate`]
+/// | `#[abistr(set = "checked")]`          | `fn set_field(&mut self, &str) -> Result<(), SetStrError>` via [`CStrBuf::try_set_str`]
+/// | `#[abistr(rename = "name")]`          | Use `name` / `set_name` instead of `field` / `set_field`
+///
+/// Additionally, `#[abistr(debug)]` on the struct itself implements [`Debug`](core::fmt::Debug), showing string fields
+/// decoded, and `#[abistr(crate = "path::to::abistr")]` can be used if `abistr` was renamed or re-exported.
+///
+/// ### Example
+///
+/// ```
+/// use abistr::*;
+///
+/// #[derive(CStrAccessors, Default)]
+/// #[abistr(debug)]
+/// #[repr(C)] struct Desc<'s> {
+///     #[abistr(get = "display", set)]                 name:       CStrBuf<u8, 16>,
+///     #[abistr(get = "str", set = "checked")]         vendor:     CStrBuf<u8, 8>,
+///     #[abistr(get = "display", rename = "path")]     path_ptr:   CStrPtr<'s, u16>,
+///     flags: u32,
+/// }
+///
+/// let mut desc = Desc::default();
+/// desc.set_name("example").unwrap();
+/// assert!(desc.set_vendor("far too long").is_err());
+/// assert_eq!(desc.name().to_string(), "example");
+/// assert_eq!(desc.vendor(), Ok(""));
+/// assert_eq!(desc.path().to_string(), "");
+/// assert_eq!(format!("{:?}", desc), r#"Desc { name: "example", vendor: "", path_ptr: "", flags: 0 }"#);
+/// ```
+pub use abistr_macros::CStrAccessors;
+
+/// Implement [`ValidateCStr`] by recursing through every field not marked `#[abistr(skip)]`.
+pub use abistr_macros::ValidateCStr;
+
+/// Implement [`IntoFfi`] for a Rust struct, converting each field to the same-named field of a C-ABI struct.
+///
+/// Requires `#[abistr(ffi = "RawType<'s>")]` on the struct, where `'s` (exactly) names the lifetime of any borrowed strings.
+/// <code>[String]</code> fields can be converted to [`CStrPtr`], [`CStrNonNull`], or [`CStrBuf`], and <code>[Option]<[String]></code>
+/// fields to [`CStrPtr`] (`None` → null) or <code>[Option]<[CStrNonNull]></code>.  Any other field is simply [`Clone`]d.
+///
+/// | Attribute                             | Effect |
+/// | ------------------------------------- | ------ |
+/// | `#[abistr(rename = "name")]`          | Convert into `raw.name` instead of `raw.field`
+/// | `#[abistr(truncate)]`                 | Truncate strings that don't fit a [`CStrBuf`] instead of returning [`IntoFfiErrorKind::BufferTooSmall`]
+/// | `#[abistr(skip)]`                     | Don't convert this field
+/// | `#[abistr(default)]` (on the struct)  | Initialize any C fields not otherwise converted via [`Default`]
+///
+/// ### Example
+///
+/// ```
+/// use abistr::*;
+///
+/// #[derive(Default)]
+/// #[repr(C)] struct RawDesc<'s> {
+///     name:   CStrPtr<'s>,
+///     path:   CStrNonNull<'s, u16>,
+///     vendor: CStrBuf<u8, 8>,
+///     flags:  u32,
+/// }
+///
+/// #[derive(IntoFfi, FromFfi)]
+/// #[abistr(ffi = "RawDesc<'s>", default)]
+/// struct Desc {
+///     name:                       String,
+///     path:                       String,
+///     #[abistr(truncate)] vendor: String,
+/// }
+///
+/// let desc = Desc { name: "example".into(), path: "a/b".into(), vendor: "far too long".into() };
+/// let guard = desc.to_ffi().unwrap();
+/// let raw : &RawDesc = guard.get(); // e.g. pass `guard.as_ptr()` to a C function
+/// assert_eq!(raw.vendor.to_units(), b"far too");
+///
+/// let copy = Desc::from_ffi(raw).unwrap();
+/// assert_eq!(copy.path, "a/b");
+/// ```
+#[cfg(feature = "alloc")] pub use abistr_macros::IntoFfi;
+
+/// Implement [`FromFfi`] for a Rust struct, copying each field from the same-named field of a C-ABI struct.
+///
+/// Requires `#[abistr(ffi = "RawType<'s>")]` on the struct, per <code>#[derive([IntoFfi](macro@IntoFfi))]</code>.
+/// Strings are decoded lossily by default, or strictly with `#[abistr(get = "str")]` (on either the struct or individual fields),
+/// returning [`FromFfiErrorKind::InvalidUnicode`] or [`FromFfiErrorKind::NotNulTerminated`] instead.
+/// `#[abistr(rename = "name")]` reads `raw.name` instead of `raw.field`, and `#[abistr(skip)]` fields are initialized via [`Default`].
+#[cfg(feature = "alloc")] pub use abistr_macros::FromFfi;
+
+use core::ffi::c_char;
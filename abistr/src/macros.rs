@@ -1,4 +1,5 @@
 #[cfg(any(doc, test))] use crate::*;
+#[cfg(test)] use core::ffi::c_char;
 
 
 #[cfg(test)] macro_rules! assert_abi_compatible {
@@ -89,6 +90,230 @@ macro_rules! cstr32 {
 }
 
 
+/// Create a <code>&[CStrNonNull]<[u8]></code> literal at compile time, encoded as Java's "Modified UTF-8" for JNI.
+///
+/// Unlike [`cstr!`], `\0` is permitted (and encoded as `C0 80`.)  Supplementary characters are encoded as surrogate pairs.
+/// Byte strings are forbidden.  See [`CodePage::Mutf8`].
+///
+/// ```
+/// # use abistr::*;
+/// let s = cstr_mutf8!("a\0\u{1F980}");
+/// assert_eq!(s.to_units(), b"a\xC0\x80\xED\xA0\xBE\xED\xB6\x80");
+/// ```
+#[cfg(doc)]
+#[macro_export]
+macro_rules! cstr_mutf8 {
+    ( $string:literal ) => {
+        $crate::abistr_macros::cstr_mutf8_impl!(($crate) $string)
+    };
+}
+
+/// Create a <code>&[CStrNonNull]<[u8]></code> literal at compile time, encoded as Java's "Modified UTF-8" for JNI.
+#[cfg(not(doc))] // use wildcards for better error messages from proc macro
+#[macro_export]
+macro_rules! cstr_mutf8 {
+    ( $($tt:tt)+ ) => {
+        $crate::abistr_macros::cstr_mutf8_impl!(($crate) $($tt)+)
+    };
+}
+
+/// Create a <code>[CUtf8NonNull]</code> literal at compile time (byte strings are forbidden)
+#[cfg(doc)]
+#[macro_export]
+macro_rules! cutf8 {
+    ( $string:literal ) => {
+        $crate::abistr_macros::cutf8_impl!(($crate) $string)
+    };
+}
+
+/// Create a <code>[CUtf8NonNull]</code> literal at compile time (byte strings are forbidden)
+#[cfg(not(doc))] // use wildcards for better error messages from proc macro
+#[macro_export]
+macro_rules! cutf8 {
+    ( $($tt:tt)+ ) => {
+        $crate::abistr_macros::cutf8_impl!(($crate) $($tt)+)
+    };
+}
+
+/// Create a <code>[CUtf16NonNull]</code> literal at compile time (byte strings are forbidden)
+#[cfg(doc)]
+#[macro_export]
+macro_rules! cutf16 {
+    ( $string:literal ) => {
+        $crate::abistr_macros::cutf16_impl!(($crate) $string)
+    };
+}
+
+/// Create a <code>[CUtf16NonNull]</code> literal at compile time (byte strings are forbidden)
+#[cfg(not(doc))] // use wildcards for better error messages from proc macro
+#[macro_export]
+macro_rules! cutf16 {
+    ( $($tt:tt)+ ) => {
+        $crate::abistr_macros::cutf16_impl!(($crate) $($tt)+)
+    };
+}
+
+
+/// Call a C function, converting any `@cstr` / `@opt_cstr` arguments via [`TryIntoAsCStr`] / [`TryIntoAsOptCStr`] first.
+///
+/// The converted temporaries are kept alive until the call returns, so there's no need for one `let` per string argument.
+/// Evaluates to <code>[Result]<R, [CallArgError]></code>, short-circuiting before the call if any argument fails to convert
+/// (e.g. a [`str`] containing an interior `\0`, or a [`CStrBuf`] missing it's terminal `\0`.)
+/// String arguments are converted in order, before any other arguments (or the callee) are evaluated.
+///
+/// The callee can be any expression - a path, or e.g. a function pointer field like `vtable.create_window`.
+///
+/// ### Example
+///
+/// ```
+/// use abistr::*;
+/// # use core::ffi::c_char;
+/// # unsafe extern "C" fn create_window(_class: *const c_char, title: *const u16, menu: *const c_char, _flags: u32) -> bool { menu.is_null() && *title == b'T' as u16 }
+/// # #[cfg(feature = "alloc")] {
+/// let ok = unsafe { ccall!(create_window(@cstr "class", @cstr cstr16!("Title"), @opt_cstr (), 42)) };
+/// assert_eq!(ok, Ok(true));
+///
+/// let err = unsafe { ccall!(create_window(@cstr "cla\0ss", @cstr cstr16!("Title"), @opt_cstr (), 42)) }.unwrap_err();
+/// assert_eq!((err.index(), err.arg()), (0, "\"cla\\0ss\""));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ccall {
+    (@callee [$($f:tt)*] ( $($args:tt)* )) => {
+        $crate::ccall!(@munch [$($f)*] [] [] $($args)*)
+    };
+    (@callee [$($f:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::ccall!(@callee [$($f)* $next] $($rest)+)
+    };
+    (@call ($f:expr) ($($done:tt)*)) => {
+        ::core::result::Result::<_, $crate::CallArgError>::Ok($f($($done)*))
+    };
+    (@munch [$($f:tt)*] [$($n:tt)*] [$($done:tt)*] ) => {
+        $crate::ccall!(@call ($($f)*) ($($done)*))
+    };
+    (@munch $f:tt [$($n:tt)*] [$($done:tt)*] @cstr $e:expr $(, $($rest:tt)*)?) => {
+        match $crate::__derive::cstr_arg(<[()]>::len(&[$($n),*]), ::core::stringify!($e), $e) {
+            ::core::result::Result::Err(err) => ::core::result::Result::Err(err),
+            ::core::result::Result::Ok(arg) => $crate::ccall!(@munch $f [$($n)* ()] [$($done)* arg.as_ptr(),] $($($rest)*)?),
+        }
+    };
+    (@munch $f:tt [$($n:tt)*] [$($done:tt)*] @opt_cstr $e:expr $(, $($rest:tt)*)?) => {
+        match $crate::__derive::opt_cstr_arg(<[()]>::len(&[$($n),*]), ::core::stringify!($e), $e) {
+            ::core::result::Result::Err(err) => ::core::result::Result::Err(err),
+            ::core::result::Result::Ok(arg) => $crate::ccall!(@munch $f [$($n)* ()] [$($done)* arg.as_ptr(),] $($($rest)*)?),
+        }
+    };
+    (@munch $f:tt [$($n:tt)*] [$($done:tt)*] $e:expr $(, $($rest:tt)*)?) => {
+        $crate::ccall!(@munch $f [$($n)* ()] [$($done)* $e,] $($($rest)*)?)
+    };
+    ( $($call:tt)+ ) => {
+        $crate::ccall!(@callee [] $($call)+)
+    };
+}
+
+/// Implement this crate's conversions for additional [`Unit`]s (typically your own.)
+///
+/// [`AsOptCStr`] is implemented for every <code>impl [AsCStr]\<U\></code>, but the remaining conversions can't be blanket impls over
+/// `U: Unit`: they would overlap with blanket impls over <code>T: [AsCStr]\<U\></code>, as any crate can implement e.g.
+/// <code>[AsCStr]\<TheirUnit\> for ()</code>.  Instead, they're implemented one [`Unit`] at a time, by this macro:
+///
+/// | Trait                                         | Implemented for |
+/// | --------------------------------------------- | --------------- |
+/// | [`AsOptCStr`]                                 | `()`, <code>[CStrPtr]\<U\></code>, <code>[Option]\<impl [AsCStr]\<U\>\></code>
+/// | [`TryIntoAsCStr`], [`TryIntoAsOptCStr`]       | <code>&[CStrBuf]\<U, N\></code>
+/// | [`TryIntoAsCStr`], [`TryIntoAsOptCStr`]       | `&str`, `String`, `Cow<str>`, `Box<str>`, `Rc<str>`, `Arc<str>`, [`WithNulPolicy`], [`Lossy`] (requires `abistr`'s `alloc` feature)
+/// | [`TryIntoAsOptCStr`]                          | `Option<&str>`, `Option<String>` (requires `abistr`'s `alloc` feature)
+///
+/// All the built-in units already have these conversions.
+/// `OsStr` and `Path` conversions are platform specific, and only implemented for [`u8`], [`u16`], and [`u32`].
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// #[derive(Clone, Copy, Default, PartialEq, Debug)]
+/// #[repr(transparent)] struct Ucs2(u16);
+/// # impl core::fmt::LowerHex for Ucs2 { fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result { self.0.fmt(f) } }
+/// # unsafe impl Unit for Ucs2 {
+/// #     type CChar = u16;
+/// #     type StrError = InteriorNulError;
+/// #     const NUL : Self = Ucs2(0);
+/// #     const EMPTY : &'static [Self; 1] = &[Ucs2(0)];
+/// #     fn decode(units: &[Self]) -> (Option<char>, usize) { (core::char::from_u32(units[0].0.into()), 1) }
+/// #     fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { buf[0] = Ucs2(if ch <= '\u{FFFF}' { ch as u16 } else { 0xFFFD }); &buf[..1] }
+/// # }
+///
+/// // unsafe impl Unit for Ucs2 { ... }
+/// impl_unit_conversions!(Ucs2);
+///
+/// fn set_title(title: impl TryIntoAsOptCStr<Ucs2>) -> bool { with_opt_cstr(title, |t| t.is_some()).unwrap_or(false) }
+/// assert!(!set_title(()));
+/// # #[cfg(feature = "alloc")] {
+/// assert!(set_title("Title"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! impl_unit_conversions {
+    ( $( $u:ty ),* $(,)? ) => {$(
+        unsafe impl $crate::AsOptCStr<$u> for () { fn as_opt_cstr(&self) -> *const <$u as $crate::Unit>::CChar { ::core::ptr::null() } }
+        unsafe impl $crate::AsOptCStr<$u> for $crate::CStrPtr<'_, $u> { fn as_opt_cstr(&self) -> *const <$u as $crate::Unit>::CChar { self.as_ptr() } }
+        unsafe impl<T: $crate::AsCStr<$u>> $crate::AsOptCStr<$u> for ::core::option::Option<T> { fn as_opt_cstr(&self) -> *const <$u as $crate::Unit>::CChar { self.as_ref().map_or(::core::ptr::null(), $crate::AsCStr::as_cstr) } }
+
+        impl<'s, const N: usize> $crate::TryIntoAsCStr<$u> for &'s $crate::CStrBuf<$u, N> {
+            type Target = $crate::CStrNonNull<'s, $u>;
+            type Error = $crate::NotNulTerminatedError;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { self.as_cstr_non_null() }
+        }
+        impl<'s, const N: usize> $crate::TryIntoAsOptCStr<$u> for &'s $crate::CStrBuf<$u, N> {
+            type Target = $crate::CStrNonNull<'s, $u>;
+            type Error = $crate::NotNulTerminatedError;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { self.as_cstr_non_null() }
+        }
+
+        $crate::__impl_unit_conversions_alloc!($u);
+    )*};
+}
+
+#[cfg(not(feature = "alloc"))] #[doc(hidden)] #[macro_export] macro_rules! __impl_unit_conversions_alloc { ( $u:ty ) => {} }
+#[cfg(feature = "alloc")] #[doc(hidden)] #[macro_export] macro_rules! __impl_unit_conversions_alloc {
+    ( $u:ty ) => {
+        $crate::__impl_unit_conversions_alloc!(@str $u, ['s] &'s str,                                   |s| $crate::SmallCString::new(s));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [] $crate::__derive::String,                    |s| $crate::SmallCString::from_string(s));
+        $crate::__impl_unit_conversions_alloc!(@str $u, ['s] $crate::__derive::Cow<'s, str>,            |s| $crate::SmallCString::from_cow(s));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [] $crate::__derive::Box<str>,                  |s| $crate::SmallCString::from_string(s.into_string()));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [] $crate::__derive::Rc<str>,                   |s| $crate::SmallCString::new(&s));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [] $crate::__derive::Arc<str>,                  |s| $crate::SmallCString::new(&s));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [S: ::core::convert::AsRef<str>] $crate::WithNulPolicy<S>, |s| $crate::SmallCString::with_nul_policy(s.string.as_ref(), s.policy));
+        $crate::__impl_unit_conversions_alloc!(@str $u, [S: ::core::convert::AsRef<str>] $crate::Lossy<S>, ::core::convert::Infallible |s| ::core::result::Result::Ok(s.into_small_cstring()));
+
+        impl<'s> $crate::TryIntoAsOptCStr<$u> for ::core::option::Option<&'s str> {
+            type Target = ::core::option::Option<$crate::SmallCString<$u>>;
+            type Error = <$u as $crate::Unit>::StrError;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { self.map($crate::SmallCString::new).transpose() }
+        }
+        impl $crate::TryIntoAsOptCStr<$u> for ::core::option::Option<$crate::__derive::String> {
+            type Target = ::core::option::Option<$crate::SmallCString<$u>>;
+            type Error = <$u as $crate::Unit>::StrError;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { self.map($crate::SmallCString::from_string).transpose() }
+        }
+    };
+    ( @str $u:ty, [$($gen:tt)*] $ty:ty, |$s:ident| $conv:expr ) => {
+        $crate::__impl_unit_conversions_alloc!(@str $u, [$($gen)*] $ty, <$u as $crate::Unit>::StrError |$s| $conv);
+    };
+    ( @str $u:ty, [$($gen:tt)*] $ty:ty, $err:ty |$s:ident| $conv:expr ) => {
+        impl<$($gen)*> $crate::TryIntoAsCStr<$u> for $ty {
+            type Target = $crate::SmallCString<$u>;
+            type Error = $err;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { let $s = self; $conv }
+        }
+        impl<$($gen)*> $crate::TryIntoAsOptCStr<$u> for $ty {
+            type Target = $crate::SmallCString<$u>;
+            type Error = $err;
+            fn try_into(self) -> ::core::result::Result<Self::Target, Self::Error> { let $s = self; $conv }
+        }
+    };
+}
+
 
 #[test] fn basics() {
     fn a(_: CStrNonNull<'static>) {}
@@ -170,6 +395,35 @@ macro_rules! cstr32 {
     b(example);
 }
 
+#[test] fn ccall() {
+    unsafe extern "C" fn strlen2(a: *const c_char, b: *const u16, c: *const u32, n: usize) -> usize {
+        let b = if b.is_null() { 0 } else { CStrNonNull::<u16>::from_ptr_unchecked_unbounded(b).to_units().len() };
+        CStrNonNull::<u8>::from_ptr_unchecked_unbounded(a).to_units().len() + b + CStrPtr::<u32>::from_ptr_unbounded(c).to_units().len() + n
+    }
+
+    assert_eq!(unsafe { ccall!(strlen2(@cstr cstr!("ab"), @opt_cstr (), @opt_cstr cstr32!("c"), 1)) }, Ok(4));
+    assert_eq!(unsafe { ccall!(strlen2(@cstr cstr!("ab"), @opt_cstr Some(cstr16!("cd")), @opt_cstr CStrPtr::NULL, 0,)) }, Ok(4));
+    let buf = unsafe { CStrBuf::<u8, 2>::from_truncate_without_nul(b"ab") };
+    let err = unsafe { ccall!(strlen2(@cstr &buf, @opt_cstr (), @opt_cstr (), 0)) }.unwrap_err();
+    assert_eq!((err.index(), err.arg(), err.error().kind()), (0, "&buf", FromUnitsWithNulErrorKind::NotNulTerminated));
+
+    struct VTable { strlen2: unsafe extern "C" fn(*const c_char, *const u16, *const u32, usize) -> usize }
+    let vtable = VTable { strlen2 };
+    assert_eq!(unsafe { ccall!(vtable.strlen2(@cstr cstr!("ab"), @opt_cstr (), @opt_cstr (), 1)) }, Ok(3));
+    assert_eq!(unsafe { ccall!(core::convert::identity(vtable.strlen2)(@cstr cstr!("a"), @opt_cstr (), @opt_cstr (), 0)) }, Ok(1));
+    let buf32 = unsafe { CStrBuf::<u32, 1>::from_truncate_without_nul(&[0x61]) };
+    let err = unsafe { ccall!(strlen2(cstr!("a").as_ptr(), core::ptr::null(), @opt_cstr &buf32, 0)) }.unwrap_err();
+    assert_eq!((err.index(), err.arg()), (2, "&buf32"));
+
+    #[cfg(feature = "alloc")] {
+        let name = alloc::string::String::from("name");
+        assert_eq!(unsafe { ccall!(strlen2(@cstr &*name, @opt_cstr "wide", @opt_cstr None::<&str>, 0)) }, Ok(8));
+        let err = unsafe { ccall!(strlen2(@cstr name, @opt_cstr "wi\0de", @opt_cstr (), 0)) }.unwrap_err();
+        assert_eq!((err.index(), err.arg(), err.error().nul_position()), (1, "\"wi\\0de\"", Some(2)));
+        assert_eq!(alloc::string::ToString::to_string(&err), "invalid argument 1 (`\"wi\\0de\"`): data provided contains an interior nul at position 2");
+    }
+}
+
 mod compile_tests {
     /// ```no_run
     /// use abistr::*;
@@ -206,4 +460,18 @@ mod compile_tests {
     /// let _ = cstr32!("\xFF");
     /// ```
     #[allow(dead_code)] struct HexAmbiguous;
+
+    /// ```no_run
+    /// use abistr::*;
+    /// let _ = cstr_mutf8!("\0");
+    /// let _ = cstr_mutf8!("\x00");
+    /// let _ = cstr_mutf8!("\u{0}");
+    /// ```
+    #[allow(dead_code)] struct Mutf8Nul;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let _ = cstr_mutf8!(b"\xFF");
+    /// ```
+    #[allow(dead_code)] struct Mutf8Bytes;
 }
@@ -0,0 +1,116 @@
+use crate::*;
+
+#[cfg(all(test, feature = "alloc"))] use alloc::string::String;
+
+
+
+/// How to handle interior `\0`s when converting a Rust string to a C string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NulPolicy {
+    /// Fail with an [`InteriorNulError`] (the default behavior of [`TryIntoAsCStr`].)
+    #[default] Error,
+    /// Truncate the string at the first `\0`.
+    TruncateAtNul,
+    /// Replace every `\0` with the given character.  Replacing with `'\0'` truncates, per [`NulPolicy::TruncateAtNul`].
+    ReplaceWith(char),
+}
+
+impl NulPolicy {
+    /// Apply `self` to `ch`, the `position`th unit of the output.  Returns `Ok(None)` to stop encoding.
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))] // only used by owned conversions
+    pub(crate) fn apply(self, ch: char, position: usize) -> Result<Option<char>, InteriorNulError> {
+        match (ch, self) {
+            ('\0', NulPolicy::Error)            => Err(InteriorNulError::new(position)),
+            ('\0', NulPolicy::TruncateAtNul)    => Ok(None),
+            ('\0', NulPolicy::ReplaceWith(ch))  => Ok(Some(ch).filter(|ch| *ch != '\0')),
+            (ch, _)                             => Ok(Some(ch)),
+        }
+    }
+}
+
+/// Convert a Rust string `S` into a C string (via [`TryIntoAsCStr`] or [`TryIntoAsOptCStr`]), handling interior `\0`s per a [`NulPolicy`].
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// fn log(_message: impl TryIntoAsCStr) { /* ... */ }
+/// # #[cfg(feature = "alloc")] {
+/// log(WithNulPolicy::replace_nul_with("user\0input", '?'));
+/// log(WithNulPolicy::truncate_at_nul(String::from("user\0input")));
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WithNulPolicy<S> {
+    /// The string to convert.
+    pub string: S,
+    /// How to handle interior `\0`s in [`Self::string`].
+    pub policy: NulPolicy,
+}
+
+impl<S> WithNulPolicy<S> {
+    /// Fail on interior `\0`s, per [`NulPolicy::Error`].
+    pub fn error(string: S) -> Self { Self { string, policy: NulPolicy::Error } }
+
+    /// Truncate at the first `\0`, per [`NulPolicy::TruncateAtNul`].
+    pub fn truncate_at_nul(string: S) -> Self { Self { string, policy: NulPolicy::TruncateAtNul } }
+
+    /// Replace every `\0` with `ch`, per [`NulPolicy::ReplaceWith`].
+    pub fn replace_nul_with(string: S, ch: char) -> Self { Self { string, policy: NulPolicy::ReplaceWith(ch) } }
+}
+
+/// Infallibly convert a Rust string `S` into a C string (with an [`Infallible`](core::convert::Infallible) [`TryIntoAsCStr::Error`]), replacing any interior `\0`s with [`U+FFFD REPLACEMENT CHARACTER`](core::char::REPLACEMENT_CHARACTER).
+///
+/// Handy for logging and UI strings, where a mangled string is preferable to an error.
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// # #[cfg(feature = "alloc")] {
+/// let title = with_cstr(Lossy("a\0b"), |s: CStrNonNull| s.to_string_lossy().into_owned()).unwrap();
+/// assert_eq!(title, "a\u{FFFD}b");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lossy<S>(pub S);
+
+impl<S> From<Lossy<S>> for WithNulPolicy<S> {
+    fn from(Lossy(string): Lossy<S>) -> Self { Self::replace_nul_with(string, core::char::REPLACEMENT_CHARACTER) }
+}
+
+#[cfg(feature = "alloc")] impl<S: AsRef<str>> Lossy<S> {
+    /// Convert `self` + `\0`, replacing any interior `\0`s (and any characters `U` can't encode, e.g. with [`Ascii`]'s `?`.)
+    pub fn into_small_cstring<U: Unit>(self) -> SmallCString<U> {
+        match SmallCString::encode(self.0.as_ref(), NulPolicy::ReplaceWith(core::char::REPLACEMENT_CHARACTER), true) {
+            Ok(s)   => s,
+            Err(_)  => unreachable!("NulPolicy::ReplaceWith never fails"),
+        }
+    }
+}
+
+// TryIntoAsCStr / TryIntoAsOptCStr impls: see `impl_unit_conversions!`
+
+
+
+#[cfg(feature = "alloc")]
+#[test] fn policies() {
+    fn units<U: Unit, S: TryIntoAsCStr<U>>(s: S) -> Result<alloc::vec::Vec<U>, InteriorNulError> where S::Error : Into<InteriorNulError> { with_cstr(s, |s: CStrNonNull<U>| s.to_units().to_vec()).map_err(Into::into) }
+
+    assert_eq!(units::<u8, _>(WithNulPolicy::error("a\0b")), Err(InteriorNulError::new(1)));
+    assert_eq!(units::<u8, _>(WithNulPolicy::truncate_at_nul("a\0b")), Ok(b"a".to_vec()));
+    assert_eq!(units::<u8, _>(WithNulPolicy::replace_nul_with("a\0b", ' ')), Ok(b"a b".to_vec()));
+    assert_eq!(units::<u8, _>(WithNulPolicy::replace_nul_with("a\0b", '\0')), Ok(b"a".to_vec()));
+    assert_eq!(units::<u8, _>(Lossy(String::from("a\0b"))), Ok("a\u{FFFD}b".as_bytes().to_vec()));
+    assert_eq!(units::<u16, _>(WithNulPolicy::replace_nul_with("a\0b", '\u{1F980}')), Ok([0x61, 0xD83E, 0xDD80, 0x62].to_vec()));
+    assert_eq!(units::<u32, _>(Lossy("a\0b")), Ok([0x61, 0xFFFD, 0x62].to_vec()));
+
+    let long = "a".repeat(100) + "\0b";
+    assert_eq!(units::<u8, _>(WithNulPolicy::truncate_at_nul(&long)).map(|u| u.len()), Ok(100));
+    assert_eq!(units::<u16, _>(WithNulPolicy::replace_nul_with(&long, 'c')).map(|u| u.len()), Ok(102));
+    assert_eq!(units::<u8, _>(WithNulPolicy::error(&long)), Err(InteriorNulError::new(100)));
+
+    fn o(s: impl TryIntoAsOptCStr) -> bool { s.try_into().is_ok() }
+    assert!(o(Lossy("a\0b")));
+    assert!(!o(WithNulPolicy::error("a\0b")));
+}
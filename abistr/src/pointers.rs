@@ -2,8 +2,9 @@ use crate::*;
 
 #[cfg(feature = "widestring")] use widestring::*;
 
-#[cfg(feature = "std")] use std::borrow::Cow;
-#[cfg(feature = "std")] use std::ffi::*;
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+#[cfg(feature = "alloc")] use alloc::string::String;
+use core::ffi::{CStr, FromBytesWithNulError};
 
 use core::fmt::{self, Debug, Formatter};
 use core::marker::PhantomData;
@@ -27,22 +28,27 @@ unsafe impl<'s, U: Unit> Sync for CStrPtr<'s, U> {}
 
 impl<'s, U: Unit> CStrPtr<'s, U> {
     /// A <code>[null]\(\)</code> [CStrPtr].
-    pub const NULL : Self = Self { ptr: 0 as *const _, phantom: PhantomData };
+    pub const NULL : Self = Self { ptr: null(), phantom: PhantomData };
 
     /// Convert a raw C-string into a [`CStrPtr`].  Note that the lifetime of the returned reference is unbounded!
     ///
     /// ### Safety
     /// *   `ptr` cannot be null
     /// *   `ptr` must point to a `\0`-terminated C string
+    /// *   `ptr` must be aligned (use [`CStrPtrUnaligned`] for strings inside `#[repr(packed)]` structs etc.)
+    ///     As a `const fn`, this can't `debug_assert!` alignment itself: debug builds instead catch misaligned pointers when they're first read
+    ///     (e.g. by [`is_empty`](Self::is_empty) or [`to_units`](Self::to_units)), or when passed to [`AnyCStrPtr::from_ptr_unbounded`].
     /// *   The underlying C-string cannot change for the duration of the lifetime `'s`.
     /// *   The lifetime `'s` is unbounded by this fn.  Very easy to accidentally extend.  Be careful!
     pub const unsafe fn from_ptr_unbounded(ptr: *const U::CChar) -> Self { Self { ptr, phantom: PhantomData } }
 
     /// Convert a raw slice of units into a [`CStrPtr`].  `units` should end with `\0`, but contain no interior `\0`s otherwise.
-    pub fn from_units_with_nul(units: &'s [U]) -> Result<Self, FromUnitsWithNulError> {
-        let (nul, interior) = units.split_last().ok_or(FromUnitsWithNulError(()))?;
-        if *nul != U::NUL { return Err(FromUnitsWithNulError(())); }
-        if interior.contains(&U::NUL) { return Err(FromUnitsWithNulError(())); }
+    pub fn from_units_with_nul(units: &'s [U]) -> Result<Self, FromUnitsWithNulError> where U : AnyUnitSequence {
+        match units.iter().position(|u| *u == U::NUL) {
+            Some(nul) if nul + 1 == units.len() => {},
+            Some(nul)   => return Err(FromUnitsWithNulError::interior_nul(nul)),
+            None        => return Err(FromUnitsWithNulError::not_nul_terminated()),
+        }
         Ok(unsafe { Self::from_ptr_unbounded(units.as_ptr().cast()) })
     }
 
@@ -50,6 +56,7 @@ impl<'s, U: Unit> CStrPtr<'s, U> {
     ///
     /// ### Safety
     /// *   `units` must contain at least one `\0`.
+    /// *   `units` must be a valid string of `U`s up to that `\0` (e.g. a <code>&\[[Utf8]\]</code> mustn't have been sliced mid-character.)
     pub unsafe fn from_units_with_nul_unchecked(units: &'s [U]) -> Self {
         debug_assert!(units.contains(&U::NUL), "Undefined Behavior: `units` contained no `\0`!");
         Self::from_ptr_unbounded(units.as_ptr() as *const _)
@@ -62,7 +69,11 @@ impl<'s, U: Unit> CStrPtr<'s, U> {
     pub fn is_null(&self) -> bool { self.ptr.is_null() }
 
     /// Checks if `self` is empty (either null, or the first character is `\0`.)
-    pub fn is_empty(&self) -> bool { self.ptr.is_null() || U::NUL == unsafe { *self.ptr.cast() } }
+    pub fn is_empty(&self) -> bool {
+        if self.ptr.is_null() { return true }
+        debug_assert!(is_aligned(self.ptr.cast::<U>()), "Undefined Behavior: C string pointer {:p} isn't aligned for its unit type (consider CStrPtrUnaligned)", self.ptr);
+        U::NUL == unsafe { *self.ptr.cast() }
+    }
 
     /// Convert `self` to a <code>&\[[Unit]\]</code> slice, **excluding** the terminal `\0`.
     ///
@@ -85,21 +96,30 @@ impl<'s, U: Unit> CStrPtr<'s, U> {
     /// Convert `self` to a <code>[Cow]\<[str]\></code>.
     ///
     /// `O(n)` to find the terminal `\0` and validate, and to convert UTF8ish data to UTF8 if necesssary.
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     pub fn to_string_lossy(&self) -> Cow<'s, str> { U::to_string_lossy(self.to_units()) }
+
+    /// Append `self` to `out`, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to find the terminal `\0` and decode.  Unlike [`to_string_lossy`](Self::to_string_lossy), this reuses `out`'s allocation.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy_into(&self, out: &mut String) { out.extend(decode_lossy(self.to_units())) }
+
+    /// Get a [`Display`](core::fmt::Display)able adapter for `self`, which decodes without allocating.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn display(&self) -> CStrDisplay<'s, U> { CStrDisplay::new(self.to_units()) }
 }
 
 impl<'s> CStrPtr<'s, u8> {
-    #[cfg(feature = "std")]
     #[doc(hidden)] pub fn from_bytes_with_nul(bytes: &'s [u8]) -> Result<Self, FromBytesWithNulError> { CStr::from_bytes_with_nul(bytes).map(Self::from) }
     #[doc(hidden)] pub unsafe fn from_bytes_with_nul_unchecked(bytes: &'s [u8]) -> Self { Self::from_units_with_nul_unchecked(bytes) }
     #[doc(hidden)] pub fn to_bytes(&self) -> &'s [u8] { self.to_units() }
     #[doc(hidden)] pub fn to_bytes_with_nul(&self) -> &'s [u8] { self.to_units_with_nul() }
 
-    /// Convert `self` to a [`std::ffi::CStr`].
+    /// Convert `self` to a [`CStr`].
     ///
     /// `O(n)` to find the terminal `\0`.
-    #[cfg(feature = "std")]
     pub fn to_cstr(&self) -> &'s CStr {
         if self.ptr.is_null() {
             unsafe { CStr::from_bytes_with_nul_unchecked(b"\0") }
@@ -112,6 +132,16 @@ impl<'s> CStrPtr<'s, u8> {
     ///
     /// `O(n)` to find the terminal `\0` and validate UTF8.
     pub fn to_str(&self) -> Result<&'s str, Utf8Error> { core::str::from_utf8(self.to_units()) }
+
+    /// Convert `self` to an [`OsStr`](std::ffi::OsStr), without any validation or transcoding.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    #[cfg(all(feature = "std", unix))] pub fn to_os_str(&self) -> &'s std::ffi::OsStr { std::os::unix::ffi::OsStrExt::from_bytes(self.to_units()) }
+
+    /// Convert `self` to a [`Path`](std::path::Path), without any validation or transcoding.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    #[cfg(all(feature = "std", unix))] pub fn to_path(&self) -> &'s std::path::Path { std::path::Path::new(self.to_os_str()) }
 }
 
 #[cfg(feature = "widestring")] impl<'s> CStrPtr<'s, u16> {
@@ -151,19 +181,23 @@ impl<'s> CStrPtr<'s, u8> {
 }
 
 impl<U: Unit> Debug for CStrPtr<'_, U> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result { U::debug(self.to_units(), f) }
+    /// Formats as a quoted string, or `null`.  `{:#?}` additionally appends the pointer address, e.g. `"example" @ 0x7ff6a1c02230`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_null() { return f.write_str("null"); }
+        U::debug(self.to_units(), f)?;
+        if f.alternate() { write!(f, " @ {:p}", self.ptr)?; }
+        Ok(())
+    }
 }
 
 impl<U: Unit> Default for CStrPtr<'_, U> {
     fn default() -> Self { Self { ptr: U::EMPTY.as_ptr().cast(), phantom: PhantomData } }
 }
 
-#[cfg(feature = "std")]
 impl<'s> From<CStrPtr<'s>> for &'s CStr {
     fn from(s: CStrPtr<'s>) -> Self { s.to_cstr() }
 }
 
-#[cfg(feature = "std")]
 impl<'s> From<&'s CStr> for CStrPtr<'s> {
     fn from(s: &'s CStr) -> Self { unsafe { CStrPtr::from_ptr_unbounded(s.as_ptr().cast()) } }
 }
@@ -189,15 +223,20 @@ impl<'s, U: Unit> CStrNonNull<'s, U> {
     /// ### Safety
     /// *   `ptr` cannot be null
     /// *   `ptr` must point to a `\0`-terminated C string
+    /// *   `ptr` must be aligned (use [`CStrPtrUnaligned`] for strings inside `#[repr(packed)]` structs etc.)
+    ///     As a `const fn`, this can't `debug_assert!` alignment itself: debug builds instead catch misaligned pointers when they're first read
+    ///     (e.g. by [`is_empty`](Self::is_empty) or [`to_units`](Self::to_units)), or when passed to [`AnyCStrPtr::from_ptr_unbounded`].
     /// *   The underlying C-string cannot change for the duration of the lifetime `'s`.
     /// *   The lifetime `'s` is unbounded by this fn.  Very easy to accidentally extend.  Be careful!
     pub const unsafe fn from_ptr_unchecked_unbounded(ptr: *const U::CChar) -> Self { Self { ptr: NonNull::new_unchecked(ptr as *mut _), phantom: PhantomData } }
 
     /// Convert a raw slice of units into a [`CStrNonNull`].  `units` should end with `\0`, but contain no interior `\0`s otherwise.
-    pub fn from_units_with_nul(units: &'s [U]) -> Result<Self, FromUnitsWithNulError> {
-        let (nul, interior) = units.split_last().ok_or(FromUnitsWithNulError(()))?;
-        if *nul != U::NUL { return Err(FromUnitsWithNulError(())); }
-        if interior.contains(&U::NUL) { return Err(FromUnitsWithNulError(())); }
+    pub fn from_units_with_nul(units: &'s [U]) -> Result<Self, FromUnitsWithNulError> where U : AnyUnitSequence {
+        match units.iter().position(|u| *u == U::NUL) {
+            Some(nul) if nul + 1 == units.len() => {},
+            Some(nul)   => return Err(FromUnitsWithNulError::interior_nul(nul)),
+            None        => return Err(FromUnitsWithNulError::not_nul_terminated()),
+        }
         Ok(unsafe { Self::from_ptr_unchecked_unbounded(units.as_ptr().cast()) })
     }
 
@@ -205,6 +244,7 @@ impl<'s, U: Unit> CStrNonNull<'s, U> {
     ///
     /// ### Safety
     /// *   `units` must contain at least one `\0`.
+    /// *   `units` must be a valid string of `U`s up to that `\0` (e.g. a <code>&\[[Utf8]\]</code> mustn't have been sliced mid-character.)
     pub unsafe fn from_units_with_nul_unchecked(units: &'s [U]) -> Self {
         debug_assert!(units.contains(&U::NUL), "Undefined Behavior: `units` contained no `\0`!");
         Self::from_ptr_unchecked_unbounded(units.as_ptr() as *const _)
@@ -223,14 +263,17 @@ impl<'s, U: Unit> CStrNonNull<'s, U> {
     pub const fn as_non_null(&self) -> NonNull<U::CChar> { self.ptr }
 
     /// Checks if `self` is empty (either <code>[null]\(\)</code>, or the first character is `\0`.)
-    pub fn is_empty(&self) -> bool { U::NUL == unsafe { *self.ptr.as_ptr().cast() } }
+    pub fn is_empty(&self) -> bool {
+        debug_assert!(is_aligned(self.ptr.as_ptr().cast::<U>()), "Undefined Behavior: C string pointer {:p} isn't aligned for its unit type (consider CStrNonNullUnaligned)", self.ptr);
+        U::NUL == unsafe { *self.ptr.as_ptr().cast() }
+    }
 
     /// Convert `self` to a <code>&\[[Unit]\]</code> slice, **excluding** the terminal `\0`.
     ///
     /// `O(n)` to find the terminal `\0`.
     pub fn to_units(&self) -> &'s [U] {
         let start = self.ptr.as_ptr().cast();
-        unsafe { core::slice::from_raw_parts(start, strlen(start) + 0) }
+        unsafe { core::slice::from_raw_parts(start, strlen(start)) }
     }
 
     /// Convert `self` to a <code>&\[[Unit]\]</code> slice, including the terminal `\0`.
@@ -244,27 +287,46 @@ impl<'s, U: Unit> CStrNonNull<'s, U> {
     /// Convert `self` to a <code>[Cow]\<[str]\></code>.
     ///
     /// `O(n)` to find the terminal `\0` and validate, and to convert UTF8ish data to UTF8 if necesssary.
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     pub fn to_string_lossy(&self) -> Cow<'s, str> { U::to_string_lossy(self.to_units()) }
+
+    /// Append `self` to `out`, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    ///
+    /// `O(n)` to find the terminal `\0` and decode.  Unlike [`to_string_lossy`](Self::to_string_lossy), this reuses `out`'s allocation.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy_into(&self, out: &mut String) { out.extend(decode_lossy(self.to_units())) }
+
+    /// Get a [`Display`](core::fmt::Display)able adapter for `self`, which decodes without allocating.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn display(&self) -> CStrDisplay<'s, U> { CStrDisplay::new(self.to_units()) }
 }
 
 impl<'s> CStrNonNull<'s, u8> {
-    #[cfg(feature = "std")]
     #[doc(hidden)] pub fn from_bytes_with_nul(bytes: &'s [u8]) -> Result<Self, FromBytesWithNulError> { CStr::from_bytes_with_nul(bytes).map(Self::from) }
     #[doc(hidden)] pub unsafe fn from_bytes_with_nul_unchecked(bytes: &'s [u8]) -> Self { Self::from_units_with_nul_unchecked(bytes) }
     #[doc(hidden)] pub fn to_bytes(&self) -> &'s [u8] { self.to_units() }
     #[doc(hidden)] pub fn to_bytes_with_nul(&self) -> &'s [u8] { self.to_units_with_nul() }
 
-    /// Convert `self` to a [`std::ffi::CStr`].
+    /// Convert `self` to a [`CStr`].
     ///
     /// `O(n)` to find the terminal `\0`.
-    #[cfg(feature = "std")]
     pub fn to_cstr(&self) -> &'s CStr { unsafe { CStr::from_ptr(self.as_ptr()) } }
 
     /// Convert `self` to a <code>&[str]</code>.
     ///
     /// `O(n)` to find the terminal `\0` and validate UTF8.
     pub fn to_str(&self) -> Result<&'s str, Utf8Error> { core::str::from_utf8(self.to_units()) }
+
+    /// Convert `self` to an [`OsStr`](std::ffi::OsStr), without any validation or transcoding.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    #[cfg(all(feature = "std", unix))] pub fn to_os_str(&self) -> &'s std::ffi::OsStr { std::os::unix::ffi::OsStrExt::from_bytes(self.to_units()) }
+
+    /// Convert `self` to a [`Path`](std::path::Path), without any validation or transcoding.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    #[cfg(all(feature = "std", unix))] pub fn to_path(&self) -> &'s std::path::Path { std::path::Path::new(self.to_os_str()) }
 }
 
 #[cfg(feature = "widestring")] impl<'s> CStrNonNull<'s, u16> {
@@ -292,19 +354,22 @@ impl<'s> CStrNonNull<'s, u8> {
 }
 
 impl<U: Unit> Debug for CStrNonNull<'_, U> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result { U::debug(self.to_units(), f) }
+    /// Formats as a quoted string.  `{:#?}` additionally appends the pointer address, e.g. `"example" @ 0x7ff6a1c02230`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        U::debug(self.to_units(), f)?;
+        if f.alternate() { write!(f, " @ {:p}", self.ptr)?; }
+        Ok(())
+    }
 }
 
 impl<U: Unit> Default for CStrNonNull<'_, U> {
     fn default() -> Self { Self { ptr: unsafe { NonNull::new_unchecked(U::EMPTY.as_ptr() as *mut _) }, phantom: PhantomData } }
 }
 
-#[cfg(feature = "std")]
 impl<'s> From<CStrNonNull<'s>> for &'s CStr {
     fn from(s: CStrNonNull<'s>) -> Self { s.to_cstr() }
 }
 
-#[cfg(feature = "std")]
 impl<'s> From<&'s CStr> for CStrNonNull<'s> {
     fn from(s: &'s CStr) -> Self { unsafe { CStrNonNull::from_ptr_unchecked_unbounded(s.as_ptr().cast()) } }
 }
@@ -323,6 +388,7 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
 
 
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn struct_interop_narrow() {
     use crate::*;
     use core::mem::*;
@@ -411,7 +477,7 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
     assert_eq!(r2.example       .as_ref().map_or(&b"\0"[..], |s| s.to_bytes_with_nul()), &b"example\0"[..]);
     assert_eq!(r2.not_unicode   .as_ref().map_or(&b"\0"[..], |s| s.to_bytes_with_nul()), &b"\xFF\xFF\0"[..]);
 
-    #[cfg(feature = "std")] {
+    {
         assert_eq!(r1.null          .to_cstr(), CStr::from_bytes_with_nul(b"\0").unwrap());
         assert_eq!(r1.empty         .to_cstr(), CStr::from_bytes_with_nul(b"\0").unwrap());
         assert_eq!(r1.example       .to_cstr(), CStr::from_bytes_with_nul(b"example\0").unwrap());
@@ -445,7 +511,20 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
         assert_eq!(r2.example       .as_ref().map_or(Cow::Borrowed(""), |s| s.to_string_lossy()), "example");
         assert_eq!(r2.not_unicode   .as_ref().map_or(Cow::Borrowed(""), |s| s.to_string_lossy()), "\u{FFFD}\u{FFFD}");
 
-        assert_eq!(format!("{:?}", r1.null          ), "\"\"" );
+        let mut out = String::from("prefix:");
+        r1.example      .to_string_lossy_into(&mut out);
+        r1.not_unicode  .to_string_lossy_into(&mut out);
+        r2.not_unicode  .as_ref().unwrap().to_string_lossy_into(&mut out);
+        assert_eq!(out, "prefix:example\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}");
+
+        assert_eq!(format!("{}", r1.null        .display()), "" );
+        assert_eq!(format!("{}", r1.empty       .display()), "" );
+        assert_eq!(format!("{}", r1.example     .display()), "example" );
+        assert_eq!(format!("{}", r1.not_unicode .display()), "\u{FFFD}\u{FFFD}" );
+        assert_eq!(format!("{}", r2.example     .unwrap().display()), "example" );
+        assert_eq!(format!("{}", r2.not_unicode .unwrap().display()), "\u{FFFD}\u{FFFD}" );
+
+        assert_eq!(format!("{:?}", r1.null          ), "null" );
         assert_eq!(format!("{:?}", r1.empty         ), "\"\"" );
         assert_eq!(format!("{:?}", r1.example       ), "\"example\"" );
         assert_eq!(format!("{:?}", r1.not_unicode   ), "\"\\xff\\xff\"" );
@@ -457,6 +536,7 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
     }
 }
 
+#[allow(clippy::bool_assert_comparison)] // table of `assert_eq!`s
 #[test] fn struct_interop_wide() {
     use core::mem::*;
 
@@ -591,7 +671,20 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
         assert_eq!(r2.example       .as_ref().map_or(Cow::Borrowed(""), |s| s.to_string_lossy()), "example");
         assert_eq!(r2.not_unicode   .as_ref().map_or(Cow::Borrowed(""), |s| s.to_string_lossy()), "\u{FFFD}\u{FFFD}");
 
-        assert_eq!(format!("{:?}", r1.null          ), "\"\"" );
+        let mut out = String::from("prefix:");
+        r1.example      .to_string_lossy_into(&mut out);
+        r1.not_unicode  .to_string_lossy_into(&mut out);
+        r2.not_unicode  .as_ref().unwrap().to_string_lossy_into(&mut out);
+        assert_eq!(out, "prefix:example\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}");
+
+        assert_eq!(format!("{}", r1.null        .display()), "" );
+        assert_eq!(format!("{}", r1.empty       .display()), "" );
+        assert_eq!(format!("{}", r1.example     .display()), "example" );
+        assert_eq!(format!("{}", r1.not_unicode .display()), "\u{FFFD}\u{FFFD}" );
+        assert_eq!(format!("{}", r2.example     .unwrap().display()), "example" );
+        assert_eq!(format!("{}", r2.not_unicode .unwrap().display()), "\u{FFFD}\u{FFFD}" );
+
+        assert_eq!(format!("{:?}", r1.null          ), "null" );
         assert_eq!(format!("{:?}", r1.empty         ), "\"\"" );
         assert_eq!(format!("{:?}", r1.example       ), "\"example\"" );
         assert_eq!(format!("{:?}", r1.not_unicode   ), "\"\\udc00\\udc00\"" );
@@ -603,6 +696,39 @@ impl<'s> From<&'s CStr> for CStrNonNull<'s> {
     }
 }
 
+#[cfg(feature = "std")]
+#[test] fn debug() {
+    let narrow = CStrNonNull::from_units_with_nul("\u{301}日本語 e\u{301}\t\"'\\\x01\0".as_bytes()).unwrap();
+    assert_eq!(format!("{:?}", narrow), "\"\\u{301}日本語 e\u{301}\\t\\\"\\'\\\\\\u{1}\"");
+
+    let invalid = CStrPtr::from_units_with_nul(b"a\xE2\x82b\xFF\0").unwrap();
+    assert_eq!(format!("{:?}", invalid), "\"a\\xe2\\x82b\\xff\"");
+
+    let wide = [b'a' as u16, 0x65E5, 0xD83D, 0xDE00, 0xDC00, 0xD800, b'b' as u16, 0];
+    let wide = CStrPtr::from_units_with_nul(&wide[..]).unwrap();
+    assert_eq!(format!("{:?}", wide), "\"a日\u{1F600}\\udc00\\ud800b\"");
+
+    let wide32 = [b'a' as u32, 0x65E5, 0x1F600, 0xD800, 0x110000, 0];
+    let wide32 = CStrPtr::from_units_with_nul(&wide32[..]).unwrap();
+    assert_eq!(format!("{:?}", wide32), "\"a日\u{1F600}\\u{d800}\\u{110000}\"");
+
+    assert_eq!(format!("{:?}",  CStrPtr::<u8>::NULL), "null");
+    assert_eq!(format!("{:#?}", CStrPtr::<u8>::NULL), "null");
+    assert_eq!(format!("{:?}",  CStrPtr::<u8>::default()), "\"\"");
+    assert_eq!(format!("{:#?}", invalid), format!("\"a\\xe2\\x82b\\xff\" @ {:p}", invalid.as_ptr()));
+    let example = cstr!("example");
+    assert_eq!(format!("{:#?}", example), format!("\"example\" @ {:p}", example.as_ptr()));
+}
+
+#[test] fn from_units_with_nul() {
+    assert_eq!(CStrPtr::from_units_with_nul(b"abc\0").map(|s| s.to_bytes()), Ok(&b"abc"[..]));
+    assert_eq!(CStrPtr::from_units_with_nul(b"").unwrap_err().kind(), FromUnitsWithNulErrorKind::NotNulTerminated);
+    assert_eq!(CStrPtr::from_units_with_nul(b"abc").unwrap_err().kind(), FromUnitsWithNulErrorKind::NotNulTerminated);
+    assert_eq!(CStrPtr::from_units_with_nul(b"a\0bc\0").unwrap_err().kind(), FromUnitsWithNulErrorKind::InteriorNul { position: 1 });
+    assert_eq!(CStrNonNull::from_units_with_nul(&[1u16, 2, 0, 0][..]).unwrap_err().nul_position(), Some(2));
+    assert_eq!(CStrNonNull::from_units_with_nul(&[1u32, 2][..]).unwrap_err().nul_position(), None);
+}
+
 #[cfg(feature = "std")] #[allow(dead_code)] mod cstrptr_lifetime_tests {
     /// ```no_run
     /// use abistr::*;
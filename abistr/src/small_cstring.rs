@@ -0,0 +1,184 @@
+use crate::*;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::any::TypeId;
+use core::fmt::{self, Debug, Formatter};
+use core::mem::ManuallyDrop;
+
+
+
+const INLINE : usize = 64;
+
+/// An owned, `\0`-terminated string of [`Unit`]s, stored inline for strings of up to [`SmallCString::INLINE_CAPACITY`] units.
+///
+/// This is the [`TryIntoAsCStr::Target`] for [`str`] and [`String`], avoiding heap allocation for short strings (e.g. identifiers)
+/// in hot FFI paths.  Longer strings fall back on a single heap allocation.
+#[derive(Clone)]
+pub struct SmallCString<U: Unit = u8>(Repr<U>);
+
+#[derive(Clone)]
+enum Repr<U: Unit> {
+    Inline { units: [U; INLINE], len: u8 },
+    Heap(Box<[U]>), // includes the terminal `\0`
+}
+
+impl<U: Unit> SmallCString<U> {
+    /// The maximum number of units (excluding the terminal `\0`) stored without allocating.
+    pub const INLINE_CAPACITY : usize = INLINE - 1;
+
+    /// Encode `s` (transcoding to [`Unit`] as necessary) + `\0`.  Fails if `s` contains any interior `\0`s, or characters `U` can't encode (see [`Unit::check_char`].)
+    pub fn new(s: &str) -> Result<Self, U::StrError> { Self::with_nul_policy(s, NulPolicy::Error) }
+
+    /// Encode `s` (transcoding to [`Unit`] as necessary) + `\0`, handling any interior `\0`s per `policy`.  Fails on characters `U` can't encode (see [`Unit::check_char`].)
+    pub fn with_nul_policy(s: &str, policy: NulPolicy) -> Result<Self, U::StrError> { Self::encode(s, policy, false) }
+
+    /// Encode `s` + `\0` per `policy`.  If `lossy`, characters `U` can't encode are replaced (per [`Unit::encode_char`]) instead of failing.
+    pub(crate) fn encode(s: &str, policy: NulPolicy, lossy: bool) -> Result<Self, U::StrError> {
+        let mut units = [U::NUL; INLINE];
+        let mut len = 0;
+        let mut buf = [U::NUL; 4];
+        for (i, ch) in s.char_indices() {
+            let ch = match policy.apply(ch, len)? { Some(ch) => ch, None => break };
+            if !lossy { U::check_char(ch, len)?; }
+            let encoded = U::encode_char(ch, &mut buf);
+            if len + encoded.len() > Self::INLINE_CAPACITY { return Self::encode_heap(&units[..len], &s[i..], policy, lossy) }
+            units[len .. len + encoded.len()].copy_from_slice(encoded);
+            len += encoded.len();
+        }
+        Ok(Self(Repr::Inline { units, len: len as u8 }))
+    }
+
+    fn encode_heap(prefix: &[U], rest: &str, policy: NulPolicy, lossy: bool) -> Result<Self, U::StrError> {
+        let mut units = Vec::with_capacity(prefix.len() + rest.len() + 1);
+        units.extend_from_slice(prefix);
+        let mut buf = [U::NUL; 4];
+        for ch in rest.chars() {
+            let ch = match policy.apply(ch, units.len())? { Some(ch) => ch, None => break };
+            if !lossy { U::check_char(ch, units.len())?; }
+            units.extend_from_slice(U::encode_char(ch, &mut buf));
+        }
+        units.push(U::NUL);
+        Ok(Self(Repr::Heap(units.into_boxed_slice())))
+    }
+
+    /// Copy `units` + `\0`.  Fails if `units` contains any `\0`s.
+    pub fn from_units(units: &[U]) -> Result<Self, InteriorNulError> where U : AnyUnitSequence { Self::from_unit_iter(units.iter().copied()) }
+
+    /// Collect `units` + `\0`.  Fails if `units` contains any `\0`s.
+    pub(crate) fn from_unit_iter(units: impl Iterator<Item = U>) -> Result<Self, InteriorNulError> {
+        let mut inline = [U::NUL; INLINE];
+        let mut heap = Vec::new();
+        for (i, unit) in units.enumerate() {
+            if unit == U::NUL { return Err(InteriorNulError::new(i)) }
+            if i < Self::INLINE_CAPACITY {
+                inline[i] = unit;
+            } else {
+                if heap.is_empty() { heap.extend_from_slice(&inline[..i]); }
+                heap.push(unit);
+            }
+        }
+        if heap.is_empty() {
+            let len = inline.iter().position(|u| *u == U::NUL).unwrap_or(Self::INLINE_CAPACITY);
+            Ok(Self(Repr::Inline { units: inline, len: len as u8 }))
+        } else {
+            heap.push(U::NUL);
+            Ok(Self(Repr::Heap(heap.into_boxed_slice())))
+        }
+    }
+
+    /// Returns `true` if `self` is stored inline (without a heap allocation.)
+    pub fn is_inline(&self) -> bool { matches!(self.0, Repr::Inline { .. }) }
+
+    /// Convert `self` to a <code>&\[[Unit]\]</code> slice, **excluding** the terminal `\0`.
+    pub fn to_units(&self) -> &[U] { let units = self.to_units_with_nul(); &units[..units.len()-1] }
+
+    /// Convert `self` to a <code>&\[[Unit]\]</code> slice, including the terminal `\0`.
+    pub fn to_units_with_nul(&self) -> &[U] {
+        match &self.0 {
+            Repr::Inline { units, len } => &units[..=usize::from(*len)],
+            Repr::Heap(units)           => units,
+        }
+    }
+
+    /// Borrow `self` as a [`CStrNonNull`].
+    pub fn as_non_null(&self) -> CStrNonNull<'_, U> { unsafe { CStrNonNull::from_units_with_nul_unchecked(self.to_units_with_nul()) } }
+
+    /// Treat `self` as a raw C string.
+    pub fn as_ptr(&self) -> *const U::CChar { self.to_units_with_nul().as_ptr().cast() }
+
+    /// Encode `s` (transcoding to [`Unit`] as necessary) + `\0`, per [`new`](Self::new).
+    ///
+    /// For [`u8`] units, reuses `s`'s heap allocation if it's too long to store inline.
+    pub fn from_string(s: String) -> Result<Self, U::StrError> {
+        if TypeId::of::<U>() != TypeId::of::<u8>() { return Self::new(&s) }
+        let s = ManuallyDrop::new(SmallCString::<u8>::from_vec(s.into_bytes())?);
+        Ok(unsafe { core::mem::transmute_copy::<SmallCString<u8>, Self>(&s) }) // U == u8
+    }
+
+    /// Encode `s` (transcoding to [`Unit`] as necessary) + `\0`, per [`new`](Self::new) or [`from_string`](Self::from_string).
+    pub fn from_cow(s: Cow<str>) -> Result<Self, U::StrError> {
+        match s {
+            Cow::Borrowed(s)    => Self::new(s),
+            Cow::Owned(s)       => Self::from_string(s),
+        }
+    }
+}
+
+impl SmallCString<u8> {
+    /// Convert `units` + `\0`, reusing it's heap allocation if it's too long to store inline.
+    pub fn from_vec(mut units: Vec<u8>) -> Result<Self, InteriorNulError> {
+        if units.len() <= Self::INLINE_CAPACITY { return Self::from_units(&units) }
+        if let Some(nul) = units.iter().position(|b| *b == 0) { return Err(InteriorNulError::new(nul)) }
+        units.push(0);
+        Ok(Self(Repr::Heap(units.into_boxed_slice())))
+    }
+}
+
+impl<U: Unit> Debug for SmallCString<U> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { U::debug(self.to_units(), fmt) }
+}
+
+unsafe impl<U: Unit> AsCStr<U> for SmallCString<U> { fn as_cstr(&self) -> *const U::CChar { self.as_ptr() } }
+
+
+
+#[test] fn small_cstring() {
+    let s = SmallCString::<u8>::new("abc").unwrap();
+    assert!(s.is_inline());
+    assert_eq!(s.to_units_with_nul(), b"abc\0");
+
+    let max = "a".repeat(SmallCString::<u8>::INLINE_CAPACITY);
+    assert!(SmallCString::<u8>::new(&max).unwrap().is_inline());
+    let long = "a".repeat(SmallCString::<u8>::INLINE_CAPACITY + 1);
+    assert!(!SmallCString::<u8>::new(&long).unwrap().is_inline());
+    assert_eq!(SmallCString::<u8>::new(&long).unwrap().to_units(), long.as_bytes());
+    assert!(!SmallCString::<u8>::from_string(long.clone()).unwrap().is_inline());
+    assert_eq!(SmallCString::<u8>::from_string(long.clone()).unwrap().to_units(), long.as_bytes());
+    assert_eq!(SmallCString::<u16>::from_string(long.clone()).unwrap().to_units().len(), long.len());
+    assert_eq!(SmallCString::<u32>::from_cow(Cow::Owned("ab".into())).unwrap().to_units(), &[0x61, 0x62]);
+    assert_eq!(SmallCString::<u8>::from_cow(Cow::Borrowed("a\0")).unwrap_err(), InteriorNulError::new(1));
+
+    // transcoding: 3 UTF8 bytes, but 1 UTF16 unit each
+    let cjk = "\u{4E2D}".repeat(SmallCString::<u16>::INLINE_CAPACITY);
+    assert!(SmallCString::<u16>::new(&cjk).unwrap().is_inline());
+    assert!(SmallCString::<u32>::new(&cjk).unwrap().is_inline());
+    assert!(!SmallCString::<u8>::new(&cjk).unwrap().is_inline());
+    assert_eq!(SmallCString::<u16>::new("\u{1F980}").unwrap().to_units(), &[0xD83E, 0xDD80]);
+    let mut spill = "a".repeat(SmallCString::<u16>::INLINE_CAPACITY - 1);
+    spill.push('\u{1F980}');
+    assert_eq!(SmallCString::<u16>::new(&spill).unwrap().to_units().len(), SmallCString::<u16>::INLINE_CAPACITY + 1);
+
+    assert_eq!(SmallCString::<u16>::from_units(&[1, 2, 3]).unwrap().to_units_with_nul(), &[1, 2, 3, 0]);
+    assert!(SmallCString::<u16>::from_units(&[1; 63]).unwrap().is_inline());
+    assert_eq!(SmallCString::<u16>::from_units(&[1; 64]).unwrap().to_units(), &[1; 64][..]);
+    assert_eq!(SmallCString::<u16>::from_units(&[1, 0]).unwrap_err(), InteriorNulError::new(1));
+
+    assert_eq!(SmallCString::<u8 >::new("a\0b").unwrap_err(), InteriorNulError::new(1));
+    assert_eq!(SmallCString::<u16>::new("\u{1F980}\0").unwrap_err(), InteriorNulError::new(2));
+    assert_eq!(SmallCString::<u8 >::new(&(long.clone() + "\0")).unwrap_err(), InteriorNulError::new(long.len()));
+    assert_eq!(SmallCString::<u8>::from_string(long.clone() + "\0").unwrap_err(), InteriorNulError::new(long.len()));
+}
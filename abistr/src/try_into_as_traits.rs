@@ -1,100 +1,226 @@
-use crate::{c_char, *};
+use crate::*;
 
-#[cfg(feature = "std")] use std::ffi::*;
+#[cfg(any(test, doc))] #[cfg(feature = "alloc")] use alloc::ffi::CString;
+#[cfg(all(test, feature = "std"))] use alloc::borrow::Cow;
+#[cfg(all(test, feature = "std"))] use alloc::boxed::Box;
+#[cfg(all(test, feature = "std"))] use alloc::rc::Rc;
+#[cfg(all(test, feature = "alloc"))] use alloc::string::String;
+#[cfg(all(test, feature = "std"))] use alloc::sync::Arc;
+#[cfg(feature = "std")] use std::ffi::{OsStr, OsString};
+#[cfg(feature = "std")] use std::path::{Path, PathBuf};
+#[cfg(doc)] use core::ffi::CStr;
+use core::convert::Infallible;
 
 
 
-/// Converts `self` ([str]/[String]/[CStr]/[CString]) into something that implements [AsCStr]
-pub trait TryIntoAsCStr<C = c_char> {
+/// Converts `self` ([str]/[String]/[CStr]/[CString]/<code>&[CStrBuf]</code>, or `Cow<str>`/`Box<str>`/`Rc<str>`/`Arc<str>`/`OsStr`/`Path`/...) into something that implements [AsCStr]
+///
+/// Conversions for your own [`Unit`]s can be implemented with [`impl_unit_conversions!`].
+pub trait TryIntoAsCStr<U: Unit = u8> {
     /// The temporary type that can be treated as a C-string.
-    type Target : AsCStr<C>;
+    type Target : AsCStr<U>;
+
+    /// Why the conversion might fail: typically [`InteriorNulError`], or [`Infallible`] for types that are already C strings.
+    type Error;
 
     /// Attempt to convert to [Self::Target].  May fail if `self` contains `\0`s.
-    fn try_into(self) -> Result<Self::Target, InteriorNulError>;
+    fn try_into(self) -> Result<Self::Target, Self::Error>;
 }
 
-impl<C, T: AsCStr<C>> TryIntoAsCStr<C> for T {
+impl<U: Unit, T: AsCStr<U>> TryIntoAsCStr<U> for T {
     type Target = T;
-    fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(self) }
+    type Error = Infallible;
+    fn try_into(self) -> Result<Self::Target, Self::Error> { Ok(self) }
 }
 
-#[cfg(feature = "std")] impl TryIntoAsCStr<c_char> for &'_ str { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "std")] impl TryIntoAsCStr<u8    > for &'_ str { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "widestring")] impl TryIntoAsCStr<u16   > for &'_ str { type Target = widestring::U16CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-#[cfg(feature = "widestring")] impl TryIntoAsCStr<u32   > for &'_ str { type Target = widestring::U32CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-
-#[cfg(feature = "std")] impl TryIntoAsCStr<c_char> for String { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "std")] impl TryIntoAsCStr<u8    > for String { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "widestring")] impl TryIntoAsCStr<u16   > for String { type Target = widestring::U16CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-#[cfg(feature = "widestring")] impl TryIntoAsCStr<u32   > for String { type Target = widestring::U32CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-
 
 
-/// Converts `self` ([str]/[String]/[CStr]/[CString]/\(\)) into something that implements [AsOptCStr]
-pub trait TryIntoAsOptCStr<C = c_char> {
+/// Converts `self` ([str]/[String]/[CStr]/[CString]/<code>&[CStrBuf]</code>/\(\), or `Cow<str>`/`Box<str>`/`Rc<str>`/`Arc<str>`/`OsStr`/`Path`/...) into something that implements [AsOptCStr]
+///
+/// Conversions for your own [`Unit`]s can be implemented with [`impl_unit_conversions!`].
+pub trait TryIntoAsOptCStr<U: Unit = u8> {
     /// The temporary type that can be treated as an [Option]al C-string.
-    type Target : AsOptCStr<C>;
+    type Target : AsOptCStr<U>;
+
+    /// Why the conversion might fail: typically [`InteriorNulError`], or [`Infallible`] for types that are already C strings.
+    type Error;
 
     /// Attempt to convert to [Self::Target].  May fail if `self` contains `\0`s.
-    fn try_into(self) -> Result<Self::Target, InteriorNulError>;
+    fn try_into(self) -> Result<Self::Target, Self::Error>;
 }
 
-impl<C, T: AsOptCStr<C>> TryIntoAsOptCStr<C> for T {
+impl<U: Unit, T: AsOptCStr<U>> TryIntoAsOptCStr<U> for T {
     type Target = T;
-    fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(self) }
+    type Error = Infallible;
+    fn try_into(self) -> Result<Self::Target, Self::Error> { Ok(self) }
 }
 
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<c_char> for &'_ str { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<u8    > for &'_ str { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u16> for &'_ str { type Target = widestring::U16CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u32> for &'_ str { type Target = widestring::U32CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
 
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<c_char> for String { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<u8    > for String { type Target = CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Ok(CString::new(self)?) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u16> for String { type Target = widestring::U16CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u32> for String { type Target = widestring::U32CString; fn try_into(self) -> Result<Self::Target, InteriorNulError> { Self::Target::from_str(self).map_err(|_| InteriorNulError(())) } }
 
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<c_char> for Option<&'_ str> { type Target = Option<CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| Ok(Some(CString::new(s)?))) } }
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<u8    > for Option<&'_ str> { type Target = Option<CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| Ok(Some(CString::new(s)?))) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u16> for Option<&'_ str> { type Target = Option<widestring::U16CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| widestring::U16CString::from_str(s).map(|s| Some(s)).map_err(|_| InteriorNulError(()))) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u32> for Option<&'_ str> { type Target = Option<widestring::U32CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| widestring::U32CString::from_str(s).map(|s| Some(s)).map_err(|_| InteriorNulError(()))) } }
+/// Implements [`TryIntoAsCStr`] and [`TryIntoAsOptCStr`] for an OS string type, converting to a [`SmallCString`] of each unit.
+/// Unlike the conversions in [`impl_unit_conversions!`], these pass platform specific encodings through as-is where possible.
+#[cfg(feature = "std")] macro_rules! os_str_impls {
+    ( $( impl[$($gen:tt)*] $ty:ty { u8: |$s8:ident| $u8:expr, u16: |$s16:ident| $u16:expr, u32: |$s32:ident| $u32:expr $(,)? } )* ) => {$(
+        os_str_impls!(@impl [$($gen)*] $ty, u8,  |$s8| $u8);
+        os_str_impls!(@impl [$($gen)*] $ty, u16, |$s16| $u16);
+        os_str_impls!(@impl [$($gen)*] $ty, u32, |$s32| $u32);
+    )*};
+    ( @impl [$($gen:tt)*] $ty:ty, $u:ty, |$s:ident| $conv:expr ) => {
+        impl<$($gen)*> TryIntoAsCStr<$u> for $ty { type Target = SmallCString<$u>; type Error = OsStrError; fn try_into(self) -> Result<Self::Target, Self::Error> { let $s = self; $conv } }
+        impl<$($gen)*> TryIntoAsOptCStr<$u> for $ty { type Target = SmallCString<$u>; type Error = OsStrError; fn try_into(self) -> Result<Self::Target, Self::Error> { let $s = self; $conv } }
+    };
+}
+
+#[cfg(feature = "std")] os_str_impls! {
+    impl['s] &'s OsStr  { u8: |s| os_str_to_u8(s),                     u16: |s| os_str_to_u16(s),                 u32: |s| os_str_to_u32(s) }
+    impl['s] &'s Path   { u8: |s| os_str_to_u8(s.as_os_str()),         u16: |s| os_str_to_u16(s.as_os_str()),     u32: |s| os_str_to_u32(s.as_os_str()) }
+    impl[] OsString     { u8: |s| os_string_to_u8(s),                  u16: |s| os_str_to_u16(&s),                u32: |s| os_str_to_u32(&s) }
+    impl[] PathBuf      { u8: |s| os_string_to_u8(s.into_os_string()), u16: |s| os_str_to_u16(s.as_os_str()),     u32: |s| os_str_to_u32(s.as_os_str()) }
+}
 
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<c_char> for Option<String> { type Target = Option<CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| Ok(Some(CString::new(s)?))) } }
-#[cfg(feature = "std")] impl TryIntoAsOptCStr<u8    > for Option<String> { type Target = Option<CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| Ok(Some(CString::new(s)?))) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u16> for Option<String> { type Target = Option<widestring::U16CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| widestring::U16CString::from_str(s).map(|s| Some(s)).map_err(|_| InteriorNulError(()))) } }
-#[cfg(feature = "widestring")] impl TryIntoAsOptCStr<u32> for Option<String> { type Target = Option<widestring::U32CString>; fn try_into(self) -> Result<Self::Target, InteriorNulError> { self.map_or(Ok(None), |s| widestring::U32CString::from_str(s).map(|s| Some(s)).map_err(|_| InteriorNulError(()))) } }
+/// Unix paths are arbitrary bytes, passed through as-is.  Elsewhere, paths must be valid unicode, and are converted to UTF8.
+#[cfg(feature = "std")] fn os_str_to_u8(s: &OsStr) -> Result<SmallCString<u8>, OsStrError> {
+    #[cfg(unix)] { Ok(SmallCString::from_units(std::os::unix::ffi::OsStrExt::as_bytes(s))?) }
+    #[cfg(not(unix))] { Ok(SmallCString::new(s.to_str().ok_or(OsStrError::NotUnicode)?)?) }
+}
 
+#[cfg(feature = "std")] fn os_string_to_u8(s: OsString) -> Result<SmallCString<u8>, OsStrError> {
+    #[cfg(unix)] { Ok(SmallCString::from_vec(std::os::unix::ffi::OsStringExt::into_vec(s))?) }
+    #[cfg(not(unix))] { os_str_to_u8(&s) }
+}
+
+/// Windows paths are (potentially ill-formed) UTF16, passed through as-is.  Elsewhere, paths must be valid unicode, and are converted to UTF16.
+#[cfg(feature = "std")] fn os_str_to_u16(s: &OsStr) -> Result<SmallCString<u16>, OsStrError> {
+    #[cfg(windows)] { Ok(SmallCString::from_unit_iter(std::os::windows::ffi::OsStrExt::encode_wide(s))?) }
+    #[cfg(not(windows))] { Ok(SmallCString::new(s.to_str().ok_or(OsStrError::NotUnicode)?)?) }
+}
+
+/// Paths must be valid unicode to convert to UTF32.
+#[cfg(feature = "std")] fn os_str_to_u32(s: &OsStr) -> Result<SmallCString<u32>, OsStrError> { Ok(SmallCString::new(s.to_str().ok_or(OsStrError::NotUnicode)?)?) }
+
+
+/// Convert `s` to a temporary C string, and borrow it for the duration of `f`.
+///
+/// Unlike <code>s.[try_into](TryIntoAsCStr::try_into)()?.[as_cstr](AsCStr::as_cstr)()</code>, which dangles as soon as the temporary is dropped,
+/// the [`CStrNonNull`] passed to `f` cannot escape the closure.
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// # #[cfg(feature = "alloc")] {
+/// let len = with_cstr("example", |s: CStrNonNull| s.to_units().len()).unwrap();
+/// assert_eq!(len, 7);
+/// assert!(with_cstr("inter\0ior", |s: CStrNonNull| s.to_units().len()).is_err());
+/// # }
+/// ```
+pub fn with_cstr<U: Unit, S: TryIntoAsCStr<U>, R>(s: S, f: impl FnOnce(CStrNonNull<'_, U>) -> R) -> Result<R, S::Error> {
+    let s = s.try_into()?;
+    Ok(f(unsafe { CStrNonNull::from_ptr_unchecked_unbounded(s.as_cstr()) }))
+}
+
+/// Convert `s` to a temporary [Option]al C string, and borrow it for the duration of `f`.
+///
+/// Unlike <code>s.[try_into](TryIntoAsOptCStr::try_into)()?.[as_opt_cstr](AsOptCStr::as_opt_cstr)()</code>, which dangles as soon as the temporary is dropped,
+/// the <code>[Option]<[CStrNonNull]></code> passed to `f` cannot escape the closure.
+pub fn with_opt_cstr<U: Unit, S: TryIntoAsOptCStr<U>, R>(s: S, f: impl FnOnce(Option<CStrNonNull<'_, U>>) -> R) -> Result<R, S::Error> {
+    let s = s.try_into()?;
+    let ptr = s.as_opt_cstr();
+    Ok(f(if ptr.is_null() { None } else { Some(unsafe { CStrNonNull::from_ptr_unchecked_unbounded(ptr) }) }))
+}
 
 
 #[test] fn basic_usage() {
     fn f(_: impl TryIntoAsCStr) {}
-    #[cfg(feature = "std")] f("test");
+    #[cfg(feature = "alloc")] f("test");
     f(cstr!("test"));
-    #[cfg(feature = "std")] f(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap());
-    #[cfg(feature = "std")] f(String::from("test"));
-    #[cfg(feature = "std")] f(CString::new("test").unwrap());
-    #[cfg(feature = "std")] f(CString::new("test").unwrap().as_c_str());
+    f(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap());
+    #[cfg(feature = "alloc")] f(String::from("test"));
+    #[cfg(feature = "alloc")] f(CString::new("test").unwrap());
+    #[cfg(feature = "alloc")] f(CString::new("test").unwrap().as_c_str());
 
 
 
     fn o(_: impl TryIntoAsOptCStr) {}
     o(());
-    #[cfg(feature = "std")] o("test");
+    #[cfg(feature = "alloc")] o("test");
     o(cstr!("test"));
-    #[cfg(feature = "std")] o(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap());
-    #[cfg(feature = "std")] o(String::from("test"));
-    #[cfg(feature = "std")] o(CString::new("test").unwrap());
-    #[cfg(feature = "std")] o(CString::new("test").unwrap().as_c_str());
+    o(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap());
+    #[cfg(feature = "alloc")] o(String::from("test"));
+    #[cfg(feature = "alloc")] o(CString::new("test").unwrap());
+    #[cfg(feature = "alloc")] o(CString::new("test").unwrap().as_c_str());
 
-    #[cfg(feature = "std")] o(Some("test"));
+    #[cfg(feature = "alloc")] o(Some("test"));
     o(Some(cstr!("test")));
-    #[cfg(feature = "std")] o(Some(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap()));
-    #[cfg(feature = "std")] o(Some(String::from("test")));
-    #[cfg(feature = "std")] o(Some(CString::new("test").unwrap()));
-    #[cfg(feature = "std")] o(Some(CString::new("test").unwrap().as_c_str()));
+    o(Some(CStrNonNull::from_bytes_with_nul(b"test\0").unwrap()));
+    #[cfg(feature = "alloc")] o(Some(String::from("test")));
+    #[cfg(feature = "alloc")] o(Some(CString::new("test").unwrap()));
+    #[cfg(feature = "alloc")] o(Some(CString::new("test").unwrap().as_c_str()));
 
-    #[cfg(feature = "std")] o(CStrPtr::from_bytes_with_nul(b"test\0").unwrap());
+    o(CStrPtr::from_bytes_with_nul(b"test\0").unwrap());
     o(CStrPtr::NULL);
+
+
+
+    use core::ffi::c_char;
+    fn c(_: impl AsCStr<c_char>) {}
+    c(cstr!("test"));
+    c(core::ffi::CStr::from_bytes_with_nul(b"test\0").unwrap());
+    #[cfg(feature = "alloc")] c(CString::new("test").unwrap());
+
+    fn oc(_: impl TryIntoAsOptCStr<c_char>) {}
+    oc(());
+    oc(CStrPtr::<c_char>::NULL);
+    oc(Some(cstr!("test")));
+    #[cfg(feature = "alloc")] oc("test");
+
+    fn ascii(_: impl TryIntoAsOptCStr<Ascii>) {}
+    ascii(());
+    ascii(&CStrBuf::<Ascii, 4>::default());
+    #[cfg(feature = "alloc")] ascii("test");
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("caf\u{E9}", |s: CStrNonNull<Ascii>| s.as_str().len()), Err(AsciiStrError::NotAscii(NotAsciiError::new(3))));
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("ca\0f", |s: CStrNonNull<Ascii>| s.as_str().len()), Err(AsciiStrError::InteriorNul(InteriorNulError::new(2))));
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr(Lossy("caf\u{E9}"), |s: CStrNonNull<Ascii>| s.as_str().len()), Ok(4));
+}
+
+#[test] fn scoped() {
+    assert_eq!(with_cstr(cstr!("test"), |s: CStrNonNull| s.to_units().len()), Ok(4));
+    assert_eq!(with_cstr(cstr16!("test"), |s: CStrNonNull<u16>| s.to_units().len()), Ok(4));
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("test", |s: CStrNonNull| s.to_units().len()), Ok(4));
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr(String::from("a\0b"), |_: CStrNonNull| ()), Err(InteriorNulError::new(1)));
+    #[cfg(feature = "widestring")] assert_eq!(with_cstr("test", |s: CStrNonNull<u16>| s.to_units().len()), Ok(4));
+
+    assert_eq!(with_opt_cstr((), |s: Option<CStrNonNull>| s.is_none()), Ok(true));
+    assert_eq!(with_opt_cstr(CStrPtr::NULL, |s: Option<CStrNonNull>| s.is_none()), Ok(true));
+    assert_eq!(with_opt_cstr(cstr!("test"), |s: Option<CStrNonNull>| s.map(|s| s.to_units().len())), Ok(Some(4)));
+    #[cfg(feature = "alloc")] assert_eq!(with_opt_cstr(Some("test"), |s: Option<CStrNonNull>| s.map(|s| s.to_units().len())), Ok(Some(4)));
+    #[cfg(feature = "alloc")] assert_eq!(with_opt_cstr(None::<String>, |s: Option<CStrNonNull>| s.is_none()), Ok(true));
+}
+
+#[cfg(feature = "std")]
+#[test] fn more_strings() {
+    fn units<U: Unit, E>(s: impl TryIntoAsCStr<U, Error = E>) -> Result<Vec<U>, E> { with_cstr(s, |s: CStrNonNull<U>| s.to_units().to_vec()) }
+
+    assert_eq!(units::<u8,  _>(Cow::Borrowed("abc")), Ok(b"abc".to_vec()));
+    assert_eq!(units::<u16, _>(Cow::<str>::Owned("abc".into())), Ok([0x61, 0x62, 0x63].to_vec()));
+    assert_eq!(units::<u8,  _>(Box::<str>::from("a\0c")), Err(InteriorNulError::new(1)));
+    assert_eq!(units::<u32, _>(Rc::<str>::from("abc")), Ok([0x61, 0x62, 0x63].to_vec()));
+    assert_eq!(units::<u8,  _>(Arc::<str>::from("abc")), Ok(b"abc".to_vec()));
+
+    assert_eq!(units::<u8,  _>(Path::new("a/b")), Ok(b"a/b".to_vec()));
+    assert_eq!(units::<u16, _>(PathBuf::from("a/b")), Ok([0x61, 0x2F, 0x62].to_vec()));
+    assert_eq!(units::<u8,  _>(OsStr::new("a\0")), Err(OsStrError::InteriorNul(InteriorNulError::new(1))));
+    assert_eq!(units::<u32, _>(OsString::from("ab")), Ok([0x61, 0x62].to_vec()));
+
+    #[cfg(unix)] {
+        use std::os::unix::ffi::OsStrExt;
+        let not_utf8 = OsStr::from_bytes(b"\xFF/b");
+        assert_eq!(units::<u8,  _>(not_utf8), Ok(b"\xFF/b".to_vec()));
+        assert_eq!(units::<u16, _>(not_utf8), Err(OsStrError::NotUnicode));
+        assert_eq!(units::<u32, _>(Path::new(not_utf8).to_path_buf()), Err(OsStrError::NotUnicode));
+        assert_eq!(cstr!(b"\xFF/b").to_path(), Path::new(not_utf8));
+        assert_eq!(CStrPtr::NULL.to_os_str(), OsStr::new(""));
+    }
 }
 
 #[cfg(feature = "std")] #[allow(dead_code)] mod compile_tests {
@@ -136,4 +262,16 @@ impl<C, T: AsOptCStr<C>> TryIntoAsOptCStr<C> for T {
     /// f(CStrPtr::NULL);
     /// ```
     struct CStrPtrNull;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let s = with_cstr(String::from("test"), |s: CStrNonNull| s).unwrap();
+    /// ```
+    struct WithCStrEscape;
+
+    /// ```compile_fail
+    /// use abistr::*;
+    /// let s = with_opt_cstr(String::from("test"), |s: Option<CStrNonNull>| s).unwrap();
+    /// ```
+    struct WithOptCStrEscape;
 }
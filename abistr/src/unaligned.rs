@@ -0,0 +1,322 @@
+use crate::*;
+
+#[cfg(feature = "alloc")] use alloc::string::String;
+#[cfg(feature = "alloc")] use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter, Write};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ptr::*;
+
+
+
+/// <code>[CStrPtrUnaligned]&lt;[Unit]&gt;</code> is ABI compatible with <code>*const [Unit]</code>, but the pointer need not be aligned.  <code>[null]\(\)</code> is treated as an empty string.
+///
+/// [`CStrPtr`] borrows its units as <code>&\[[Unit]\]</code>, which is undefined behavior if the string lives inside a `#[repr(packed)]` struct,
+/// or at an odd offset into file contents.  [`CStrPtrUnaligned`] instead scans and copies out units with [`read_unaligned`].
+///
+/// ```
+/// # use abistr::*;
+/// #[repr(C, packed)] struct Header {
+///     tag:    u8,
+///     name:   [u16; 4],
+/// }
+///
+/// let header = Header { tag: 1, name: [0x68, 0x69, 0, 0] };
+/// let name = unsafe { CStrPtrUnaligned::<u16>::from_ptr_unbounded(core::ptr::addr_of!(header.name).cast()) };
+/// assert!(name.chars_lossy().eq("hi".chars()));
+/// assert_eq!(name.len(), 2);
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct CStrPtrUnaligned<'s, U: Unit = u8> {
+    ptr:        *const U::CChar,
+    phantom:    PhantomData<&'s U::CChar>,
+}
+
+/// <code>[Option]&lt;[CStrNonNullUnaligned]&lt;[Unit]&gt;&gt;</code> is ABI compatible with <code>*const [Unit]</code>, but the pointer need not be aligned.
+///
+/// See [`CStrPtrUnaligned`] for details.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct CStrNonNullUnaligned<'s, U: Unit = u8> {
+    ptr:        NonNull<U::CChar>,
+    phantom:    PhantomData<&'s U::CChar>,
+}
+
+unsafe impl<'s, U: Unit> Send for CStrPtrUnaligned<'s, U> {}
+unsafe impl<'s, U: Unit> Sync for CStrPtrUnaligned<'s, U> {}
+unsafe impl<'s, U: Unit> Send for CStrNonNullUnaligned<'s, U> {}
+unsafe impl<'s, U: Unit> Sync for CStrNonNullUnaligned<'s, U> {}
+
+impl<'s, U: Unit> CStrPtrUnaligned<'s, U> {
+    /// A <code>[null]\(\)</code> [CStrPtrUnaligned].
+    pub const NULL : Self = Self { ptr: null(), phantom: PhantomData };
+
+    /// Convert a raw (potentially unaligned) C-string into a [`CStrPtrUnaligned`].  Note that the lifetime of the returned reference is unbounded!
+    ///
+    /// ### Safety
+    /// *   `ptr` must be null, or point to a `\0`-terminated C string
+    /// *   The underlying C-string cannot change for the duration of the lifetime `'s`.
+    /// *   The lifetime `'s` is unbounded by this fn.  Very easy to accidentally extend.  Be careful!
+    pub const unsafe fn from_ptr_unbounded(ptr: *const U::CChar) -> Self { Self { ptr, phantom: PhantomData } }
+
+    /// Treat `self` as a raw C string.
+    pub const fn as_ptr(&self) -> *const U::CChar { self.ptr }
+
+    /// Checks if `self` is <code>[null]\(\)</code>.
+    pub fn is_null(&self) -> bool { self.ptr.is_null() }
+
+    /// Checks if `self` is empty (either <code>[null]\(\)</code>, or the first character is `\0`.)
+    pub fn is_empty(&self) -> bool { self.ptr.is_null() || U::NUL == unsafe { self.ptr.cast::<U>().read_unaligned() } }
+
+    /// Count the units of the string, **excluding** the terminal `\0`.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn len(&self) -> usize { self.units().count() }
+
+    /// Convert `self` to a [`CStrPtr`], or return [`None`] if the pointer isn't aligned.
+    pub fn to_aligned(&self) -> Option<CStrPtr<'s, U>> { if is_aligned(self.ptr.cast::<U>()) { Some(unsafe { CStrPtr::from_ptr_unbounded(self.ptr) }) } else { None } }
+
+    /// Iterate over the units of the string, **excluding** the terminal `\0`.
+    pub fn units(&self) -> CStrUnitsUnaligned<'s, U> { CStrUnitsUnaligned::new(self.ptr.cast()) }
+
+    /// Iterate over the [`char`]s of the string, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    pub fn chars_lossy(&self) -> CStrCharsLossyUnaligned<'s, U> { CStrCharsLossyUnaligned::new(self.units()) }
+
+    /// Copy the units of the string, **excluding** the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_units_vec(&self) -> Vec<U> { self.units().collect() }
+
+    /// Convert `self` to a [`String`], replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> String { self.chars_lossy().collect() }
+}
+
+impl<'s, U: Unit> CStrNonNullUnaligned<'s, U> {
+    /// Convert a raw (potentially unaligned) C-string into a [`CStrNonNullUnaligned`].  Note that the lifetime of the returned reference is unbounded!
+    ///
+    /// ### Safety
+    /// *   `ptr` cannot be null
+    /// *   `ptr` must point to a `\0`-terminated C string
+    /// *   The underlying C-string cannot change for the duration of the lifetime `'s`.
+    /// *   The lifetime `'s` is unbounded by this fn.  Very easy to accidentally extend.  Be careful!
+    pub const unsafe fn from_ptr_unchecked_unbounded(ptr: *const U::CChar) -> Self { Self { ptr: NonNull::new_unchecked(ptr as *mut _), phantom: PhantomData } }
+
+    /// Treat `self` as a raw C string.
+    pub const fn as_ptr(&self) -> *const U::CChar { self.ptr.as_ptr().cast() }
+
+    /// Treat `self` as a [`NonNull`] C string.
+    pub const fn as_non_null(&self) -> NonNull<U::CChar> { self.ptr }
+
+    /// Checks if `self` is empty (the first character is `\0`.)
+    pub fn is_empty(&self) -> bool { U::NUL == unsafe { self.ptr.as_ptr().cast::<U>().read_unaligned() } }
+
+    /// Count the units of the string, **excluding** the terminal `\0`.
+    ///
+    /// `O(n)` to find the terminal `\0`.
+    pub fn len(&self) -> usize { self.units().count() }
+
+    /// Convert `self` to a [`CStrNonNull`], or return [`None`] if the pointer isn't aligned.
+    pub fn to_aligned(&self) -> Option<CStrNonNull<'s, U>> { if is_aligned(self.as_ptr().cast::<U>()) { Some(unsafe { CStrNonNull::from_ptr_unchecked_unbounded(self.as_ptr()) }) } else { None } }
+
+    /// Iterate over the units of the string, **excluding** the terminal `\0`.
+    pub fn units(&self) -> CStrUnitsUnaligned<'s, U> { CStrUnitsUnaligned::new(self.as_ptr().cast()) }
+
+    /// Iterate over the [`char`]s of the string, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    pub fn chars_lossy(&self) -> CStrCharsLossyUnaligned<'s, U> { CStrCharsLossyUnaligned::new(self.units()) }
+
+    /// Copy the units of the string, **excluding** the terminal `\0`.
+    #[cfg(feature = "alloc")]
+    pub fn to_units_vec(&self) -> Vec<U> { self.units().collect() }
+
+    /// Convert `self` to a [`String`], replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> String { self.chars_lossy().collect() }
+}
+
+impl<const N: usize, U: Unit> CStrBuf<U, N> {
+    /// Modifies the buffer to contain a copy of `data` + `\0`.
+    /// If `data` + `\0` will not fit, <code>[Err]\([BufferTooSmallError]\)</code> will be returned without modifying the underlying buffer.
+    pub fn try_set_unaligned(&mut self, data: CStrPtrUnaligned<U>) -> Result<(), BufferTooSmallError> {
+        let len = data.len();
+        if len >= N { Err(BufferTooSmallError::new(len + 1, N))? }
+        let buffer = unsafe { self.buffer_mut() }; // ✔️ `\0`-terminated below
+        for (dst, unit) in buffer.iter_mut().zip(data.units()) { *dst = unit; }
+        buffer[len] = U::NUL;
+        Ok(())
+    }
+}
+
+/// `from_bytes_with_nul` reinterprets arbitrary bytes as units, so it's only implemented for units where any bit pattern is valid
+/// (e.g. not [`Utf8`] or [`Ascii`], which would otherwise be constructible from invalid bytes.)
+macro_rules! from_bytes_with_nul {
+    ( $( $unit:ty ),* $(,)? ) => {$(
+        impl<'s> CStrPtrUnaligned<'s, $unit> {
+            /// Convert the raw bytes of a string into a [`CStrPtrUnaligned`].  `bytes` should end with a `\0` unit, but contain no interior `\0` units otherwise.
+            pub fn from_bytes_with_nul(bytes: &'s [u8]) -> Result<Self, FromUnitsWithNulError> {
+                validate_bytes_with_nul::<$unit>(bytes)?;
+                Ok(unsafe { Self::from_ptr_unbounded(bytes.as_ptr().cast()) })
+            }
+        }
+
+        impl<'s> CStrNonNullUnaligned<'s, $unit> {
+            /// Convert the raw bytes of a string into a [`CStrNonNullUnaligned`].  `bytes` should end with a `\0` unit, but contain no interior `\0` units otherwise.
+            pub fn from_bytes_with_nul(bytes: &'s [u8]) -> Result<Self, FromUnitsWithNulError> {
+                validate_bytes_with_nul::<$unit>(bytes)?;
+                Ok(unsafe { Self::from_ptr_unchecked_unbounded(bytes.as_ptr().cast()) })
+            }
+        }
+    )*};
+}
+
+from_bytes_with_nul!(u8, u16, u32, i8, i16, i32, U16Le, U16Be, U32Le, U32Be);
+
+fn validate_bytes_with_nul<U: Unit>(bytes: &[u8]) -> Result<(), FromUnitsWithNulError> {
+    let size = core::mem::size_of::<U>();
+    if bytes.len() % size != 0 { Err(FromUnitsWithNulError::not_nul_terminated())? } // trailing partial unit
+    let units = bytes.len() / size;
+    let ptr = bytes.as_ptr().cast::<U>();
+    match (0 .. units).find(|&i| U::NUL == unsafe { ptr.add(i).read_unaligned() }) {
+        Some(nul) if nul + 1 == units => Ok(()),
+        Some(nul)   => Err(FromUnitsWithNulError::interior_nul(nul)),
+        None        => Err(FromUnitsWithNulError::not_nul_terminated()),
+    }
+}
+
+fn debug_units<U: Unit>(units: CStrUnitsUnaligned<U>, f: &mut Formatter) -> fmt::Result {
+    f.write_char('"')?;
+    let mut decoder = CStrCharsLossyUnaligned::new(units);
+    let mut first = true;
+    while let Some((ch, units, n)) = decoder.next_decoded() {
+        crate::fmt::escape_decoded(ch, &units[..n], first, f)?;
+        first = false;
+    }
+    f.write_char('"')
+}
+
+impl<U: Unit> Debug for CStrPtrUnaligned<'_, U> { fn fmt(&self, f: &mut Formatter) -> fmt::Result { debug_units(self.units(), f) } }
+impl<U: Unit> Debug for CStrNonNullUnaligned<'_, U> { fn fmt(&self, f: &mut Formatter) -> fmt::Result { debug_units(self.units(), f) } }
+
+impl<U: Unit> Default for CStrPtrUnaligned<'_, U> { fn default() -> Self { CStrPtr::default().into() } }
+impl<U: Unit> Default for CStrNonNullUnaligned<'_, U> { fn default() -> Self { CStrNonNull::default().into() } }
+
+impl<'s, U: Unit> From<CStrPtr<'s, U>>              for CStrPtrUnaligned<'s, U>     { fn from(s: CStrPtr<'s, U>             ) -> Self { unsafe { Self::from_ptr_unbounded(s.as_ptr()) } } }
+impl<'s, U: Unit> From<CStrNonNull<'s, U>>          for CStrPtrUnaligned<'s, U>     { fn from(s: CStrNonNull<'s, U>         ) -> Self { unsafe { Self::from_ptr_unbounded(s.as_ptr()) } } }
+impl<'s, U: Unit> From<CStrNonNullUnaligned<'s, U>> for CStrPtrUnaligned<'s, U>     { fn from(s: CStrNonNullUnaligned<'s, U>) -> Self { unsafe { Self::from_ptr_unbounded(s.as_ptr()) } } }
+impl<'s, U: Unit> From<CStrNonNull<'s, U>>          for CStrNonNullUnaligned<'s, U> { fn from(s: CStrNonNull<'s, U>         ) -> Self { unsafe { Self::from_ptr_unchecked_unbounded(s.as_ptr()) } } }
+
+
+
+/// Iterator over the units of a (potentially unaligned) C string, **excluding** the terminal `\0`.  Returned by [`CStrPtrUnaligned::units`].
+#[derive(Clone)]
+pub struct CStrUnitsUnaligned<'s, U: Unit> {
+    ptr:        *const U, // null once exhausted
+    phantom:    PhantomData<&'s U>,
+}
+
+impl<U: Unit> CStrUnitsUnaligned<'_, U> {
+    fn new(ptr: *const U) -> Self { Self { ptr, phantom: PhantomData } }
+}
+
+impl<U: Unit> Iterator for CStrUnitsUnaligned<'_, U> {
+    type Item = U;
+    fn next(&mut self) -> Option<U> {
+        if self.ptr.is_null() { return None }
+        let unit = unsafe { self.ptr.read_unaligned() };
+        if unit == U::NUL { self.ptr = null(); return None }
+        self.ptr = unsafe { self.ptr.add(1) };
+        Some(unit)
+    }
+}
+
+impl<U: Unit> FusedIterator for CStrUnitsUnaligned<'_, U> {}
+
+/// Iterator over the [`char`]s of a (potentially unaligned) C string, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].  Returned by [`CStrPtrUnaligned::chars_lossy`].
+#[derive(Clone)]
+pub struct CStrCharsLossyUnaligned<'s, U: Unit> {
+    units:  CStrUnitsUnaligned<'s, U>,
+    window: [U; 4], // no `Unit` decodes more than 4 units at a time
+    len:    usize,
+}
+
+impl<'s, U: Unit> CStrCharsLossyUnaligned<'s, U> {
+    fn new(units: CStrUnitsUnaligned<'s, U>) -> Self { Self { units, window: [U::NUL; 4], len: 0 } }
+
+    /// Decode the next character, returning it (or [`None`] if invalid), the units consumed, and the number of units consumed.
+    fn next_decoded(&mut self) -> Option<(Option<char>, [U; 4], usize)> {
+        while self.len < 4 {
+            match self.units.next() {
+                Some(unit)  => self.window[self.len] = unit,
+                None        => break,
+            }
+            self.len += 1;
+        }
+        if self.len == 0 { return None }
+        let (ch, n) = U::decode(&self.window[..self.len]);
+        let mut consumed = [U::NUL; 4];
+        consumed[..n].copy_from_slice(&self.window[..n]);
+        self.window.copy_within(n.., 0);
+        self.len -= n;
+        Some((ch, consumed, n))
+    }
+}
+
+impl<U: Unit> Iterator for CStrCharsLossyUnaligned<'_, U> {
+    type Item = char;
+    fn next(&mut self) -> Option<char> { self.next_decoded().map(|(ch, _, _)| ch.unwrap_or(core::char::REPLACEMENT_CHARACTER)) }
+}
+
+impl<U: Unit> FusedIterator for CStrCharsLossyUnaligned<'_, U> {}
+
+
+
+#[test] fn abi_layout() {
+    assert_abi_compatible!(CStrPtrUnaligned,                    *const c_char);
+    assert_abi_compatible!(Option<CStrNonNullUnaligned>,        *const c_char);
+    assert_abi_compatible!(CStrPtrUnaligned<u16>,               *const u16);
+    assert_abi_compatible!(Option<CStrNonNullUnaligned<u16>>,   *const u16);
+}
+
+#[test] fn unaligned() {
+    #[repr(C, align(4))] struct Aligned([u8; 13]);
+    let mut bytes = Aligned([0; 13]);
+    for (i, unit) in [0x61u16, 0xDFFF, 0, 0xD83E, 0xDD80, 0].iter().enumerate() { bytes.0[1+2*i..][..2].copy_from_slice(&unit.to_ne_bytes()); }
+    let odd = &bytes.0[1..];
+
+    let s = CStrNonNullUnaligned::<u16>::from_bytes_with_nul(&odd[..6]).unwrap();
+    assert_eq!(s.len(), 2);
+    assert!(s.to_aligned().is_none());
+    assert!(s.units().eq([0x61, 0xDFFF].iter().copied()));
+    assert!(s.chars_lossy().eq(['a', '\u{FFFD}'].iter().copied()));
+    #[cfg(feature = "alloc")] assert_eq!(alloc::format!("{:?}", s), "\"a\\udfff\"");
+    #[cfg(feature = "alloc")] assert_eq!(alloc::format!("{:?}", CStrPtrUnaligned::<u8>::from_bytes_with_nul(b"a\xFF\0").unwrap()), "\"a\\xff\"");
+
+    assert_eq!(CStrPtrUnaligned::<u16>::from_bytes_with_nul(&odd[..5]).unwrap_err(), FromUnitsWithNulError::not_nul_terminated());
+    assert_eq!(CStrPtrUnaligned::<u16>::from_bytes_with_nul(&odd[..7]).unwrap_err(), FromUnitsWithNulError::not_nul_terminated());
+    assert_eq!(CStrPtrUnaligned::<u16>::from_bytes_with_nul(&odd[..11]).unwrap_err(), FromUnitsWithNulError::not_nul_terminated());
+    assert_eq!(CStrPtrUnaligned::<u16>::from_bytes_with_nul(&odd[..12]).unwrap_err(), FromUnitsWithNulError::interior_nul(2));
+    assert_eq!(CStrPtrUnaligned::<u16>::from_bytes_with_nul(b"a\0\0\0x").unwrap_err(), FromUnitsWithNulError::not_nul_terminated());
+
+    let crab = CStrPtrUnaligned::<u16>::from_bytes_with_nul(&odd[6..]).unwrap();
+    assert!(crab.chars_lossy().eq(['\u{1F980}'].iter().copied()));
+    assert!(!crab.is_empty());
+    #[cfg(feature = "alloc")] assert_eq!(crab.to_string_lossy(), "\u{1F980}");
+    #[cfg(feature = "alloc")] assert_eq!(crab.to_units_vec(), [0xD83E, 0xDD80]);
+
+    let mut buf = CStrBuf::<u16, 3>::default();
+    buf.try_set_unaligned(crab).unwrap();
+    assert_eq!(buf.to_units(), [0xD83E, 0xDD80]);
+    assert_eq!(CStrBuf::<u16, 2>::default().try_set_unaligned(crab), Err(BufferTooSmallError::new(3, 2)));
+    let mut small = CStrBuf::<u16, 2>::from_truncate(&[0x62]);
+    assert_eq!(small.try_set_unaligned(crab), Err(BufferTooSmallError::new(3, 2)));
+    assert_eq!(small.to_units(), [0x62], "failures shouldn't modify the buffer");
+    buf.try_set_unaligned(CStrPtrUnaligned::NULL).unwrap();
+    assert_eq!(buf.to_units(), []);
+
+    let aligned = CStrPtrUnaligned::from(cstr16!("abc"));
+    assert_eq!(aligned.to_aligned().unwrap().to_units(), cstr16!("abc").to_units());
+    assert!(CStrPtrUnaligned::<u32>::NULL.is_empty());
+    assert_eq!(CStrPtrUnaligned::<u32>::NULL.len(), 0);
+    assert!(CStrNonNullUnaligned::<u32>::default().is_empty());
+    assert!(CStrPtrUnaligned::<u8>::from_bytes_with_nul(b"\xC3\xA9\0").unwrap().chars_lossy().eq(['\u{E9}'].iter().copied()));
+}
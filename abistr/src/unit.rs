@@ -1,66 +1,226 @@
-/// [`u8`]/[`u16`]/[`u32`], a rough analog to a [Unicode Code Unit](https://unicode.org/glossary/#code_unit).
+#[cfg(feature = "alloc")] use alloc::borrow::Cow;
+#[cfg(feature = "alloc")] use alloc::string::String;
+use core::fmt::{self, Formatter};
+#[cfg(test)] use crate::*;
+
+
+
+/// [`u8`]/[`u16`]/[`u32`] (or [`i8`]/[`i16`]/[`i32`]), a rough analog to a [Unicode Code Unit](https://unicode.org/glossary/#code_unit).
+///
+/// | Encoding  | Rust          | C++       | Windows   | Other     |
+/// | --------- | ------------- | --------- | --------- | --------- |
+/// | ASCII     | ~~c_char~~    | char                                    |           |                   |
+/// | UTF8      | [u8], [i8]    | [char8_t] (C++20), char, unsigned char, signed char |           |                   |
+/// | UTF16     | [u16], [i16]  | [char16_t] (C++11), uint16_t            | wchar_t   | [unichar], [jchar]|
+/// | UTF32     | [u32], [i32]  | [char32_t] (C++11), uint32_t            |           | wchar_t (signed on Linux) |
+///
+/// Signed units are decoded as their unsigned counterparts, reinterpreting the bits (e.g. `-1i8` is `0xFF`.)
+///
+/// Native byte order is assumed: for wide strings with a fixed byte order (file formats, network protocols), see [`U16Le`](crate::U16Le), [`U16Be`](crate::U16Be), [`U32Le`](crate::U32Le), and [`U32Be`](crate::U32Be).
+///
+/// N.B. JNI's `jchar`s are UTF16, but it's `const char*` strings (`GetStringUTFChars` etc.) are Java's "Modified UTF-8": see [`CodePage::Mutf8`](crate::CodePage::Mutf8) and [`cstr_mutf8!`](crate::cstr_mutf8).
+///
+/// ### Implementing [`Unit`]
+///
+/// Other code unit types - e.g. `#[repr(transparent)]` newtypes - may implement [`Unit`] themselves,
+/// providing [`StrError`](Self::StrError), [`NUL`](Self::NUL), [`EMPTY`](Self::EMPTY), [`decode`](Self::decode), and [`encode_char`](Self::encode_char).
+/// Implement [`AnyUnitSequence`] too if strings can be built from arbitrary slices of units.
+/// Conversions that can't be implemented generically over [`Unit`] (e.g. [`TryIntoAsCStr`](crate::TryIntoAsCStr) for [`str`]) can then be implemented via [`impl_unit_conversions!`](crate::impl_unit_conversions).
+///
+/// ```
+/// # use abistr::*;
+/// # use core::convert::TryFrom;
+/// #[derive(Clone, Copy, Default, PartialEq, Debug)]
+/// #[repr(transparent)] struct Latin1(u8);
+///
+/// impl core::fmt::LowerHex for Latin1 {
+///     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result { self.0.fmt(f) }
+/// }
+///
+/// unsafe impl Unit for Latin1 {
+///     type CChar = core::ffi::c_char;
+///     type StrError = InteriorNulError;
+///     const NUL : Self = Latin1(0);
+///     const EMPTY : &'static [Self; 1] = &[Latin1(0)];
+///     fn decode(units: &[Self]) -> (Option<char>, usize) { (Some(char::from(units[0].0)), 1) }
+///     fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] {
+///         buf[0] = Latin1(u8::try_from(ch).unwrap_or(b'?'));
+///         &buf[..1]
+///     }
+/// }
+///
+/// unsafe impl AnyUnitSequence for Latin1 {}
 ///
-/// | Encoding  | Rust      | C++       | Windows   | Other     |
-/// | --------- | --------- | --------- | --------- | --------- |
-/// | ASCII     | ~~c_char~~| char                                    |           |                   |
-/// | UTF8      | [u8]      | [char8_t] (C++20), char, unsigned char  |           |                   |
-/// | UTF16     | [u16]     | [char16_t] (C++11), uint16_t            | wchar_t   | [unichar], [jchar]|
-/// | UTF32     | [u32]     | [char32_t] (C++11), uint32_t            |           | wchar_t           |
+/// impl_unit_conversions!(Latin1);
+///
+/// let s = CStrNonNull::<Latin1>::from_units_with_nul(&[Latin1(0xE9), Latin1(0)]).unwrap();
+/// assert_eq!(s.chars_lossy().next(), Some('\u{E9}'));
+/// assert_eq!(with_opt_cstr((), |s: Option<CStrNonNull<Latin1>>| s.is_none()), Ok(true));
+/// ```
+///
+/// ### Safety
+///
+/// By implementing this trait, you promise that:
+///
+/// *   [`Self::CChar`] has the same size and alignment as `Self`, and any `Self` is a valid `CChar`.
+///     The reverse needn't hold: `Self` may have validity invariants of its own (e.g. [`Ascii`](crate::Ascii) is always `< 0x80`),
+///     which callers of `unsafe` constructors taking <code>\*const [CChar](Self::CChar)</code> must uphold.
+/// *   `Self` has no padding, and [`PartialEq`] compares units bitwise (C strings are scanned for [`Self::NUL`] via `==`.)
+/// *   [`Self::EMPTY`] is `[Self::NUL]`.
+/// *   [`Self::decode`] returns a unit count between `1` and `units.len()` inclusive.
+/// *   [`Self::encode_char`] never writes [`Self::NUL`] for anything but `'\0'`.
 ///
 /// [char8_t]:              https://en.cppreference.com/w/cpp/language/types#char8_t
 /// [char16_t]:             https://en.cppreference.com/w/cpp/language/types#char16_t
 /// [char32_t]:             https://en.cppreference.com/w/cpp/language/types#char32_t
 /// [jchar]:                https://docs.oracle.com/javase/7/docs/technotes/guides/jni/spec/types.html
 /// [unichar]:              https://developer.apple.com/documentation/foundation/unichar
-pub trait Unit : private::Unit {}
-impl Unit for u8  {}
-impl Unit for u16 {}
-impl Unit for u32 {}
-
-pub(crate) mod private {
-    use crate::*;
-    #[cfg(feature = "std")] use std::borrow::Cow;
-    use core::char::REPLACEMENT_CHARACTER;
-    use core::fmt::{self, Formatter};
-
-    pub trait Unit : Default + Copy + PartialEq + 'static {
-        type CChar : Copy + 'static; // XXX: eliminate?
-        const NUL : Self;
-        const EMPTY : &'static [Self; 1];
-        fn debug(buf: &[Self], fmt: &mut Formatter) -> fmt::Result;
-        #[cfg(feature = "std")] fn to_string_lossy(buf: &[Self]) -> Cow<str>;
-        fn zeroed<const N: usize>() -> [Self; N];
-    }
+pub unsafe trait Unit : Default + Copy + PartialEq + fmt::LowerHex + 'static {
+    /// The pointee of raw C strings of this unit: [`c_char`](core::ffi::c_char) for [`u8`] (for interop with `*const c_char` APIs), otherwise typically `Self`.
+    type CChar : Copy + 'static;
 
-    impl Unit for u8 {
-        type CChar = c_char;
-        const NUL : Self = 0;
-        const EMPTY : &'static [Self; 1] = &[0];
-        fn debug(buf: &[Self], fmt: &mut Formatter) -> fmt::Result { crate::fmt::cstr_bytes(buf, fmt) }
-        #[cfg(feature = "std")] fn to_string_lossy(buf: &[Self]) -> Cow<str> { String::from_utf8_lossy(buf) }
-        fn zeroed<const N: usize>() -> [Self; N] { unsafe { core::mem::zeroed() } }
-    }
+    /// Why encoding a [`str`] as `Self` might fail (e.g. via [`SmallCString::new`](crate::SmallCString::new), or [`TryIntoAsCStr`](crate::TryIntoAsCStr)):
+    /// typically [`InteriorNulError`](crate::InteriorNulError), or e.g. [`AsciiStrError`](crate::AsciiStrError) for units that can't encode every [`char`].
+    type StrError : From<crate::InteriorNulError> + Into<crate::SetStrError> + fmt::Debug;
+
+    /// The unit terminating C strings (typically `0`.)
+    const NUL : Self;
+
+    /// An empty C string (just <code>[[Self::NUL]]</code>.)
+    const EMPTY : &'static [Self; 1];
+
+    /// Decode the first character of `units` (which must be non-empty), returning it (or [`None`] if invalid) and the number of units consumed.
+    fn decode(units: &[Self]) -> (Option<char>, usize);
+
+    /// Encode `ch` into `buf`, returning the units written.
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self];
+
+    /// Fail if [`encode_char`](Self::encode_char) can't encode `ch` (at unit `position`) exactly, e.g. if it would substitute `?` instead.
+    /// Checked by fallible conversions (e.g. [`CStrBuf::try_set_str`](crate::CStrBuf::try_set_str)), but not lossy or truncating ones.  By default, any [`char`] is fine.
+    fn check_char(ch: char, position: usize) -> Result<(), Self::StrError> { let _ = (ch, position); Ok(()) }
+
+    /// Format `units` as a quoted string.  By default, invalid sequences are escaped one unit at a time with [`debug_unit`](Self::debug_unit).
+    fn debug(units: &[Self], fmt: &mut Formatter) -> fmt::Result { crate::fmt::cstr_units(units, fmt) }
+
+    /// Escape a single unit of an invalid sequence, for [`debug`](Self::debug) and e.g. <code>[CStrPtrUnaligned](crate::CStrPtrUnaligned)</code>'s [`Debug`](fmt::Debug).  By default, `\x{..}`.
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "\\x{{{:x}}}", unit) }
 
-    impl Unit for u16 {
-        type CChar = Self;
-        const NUL : Self = 0;
-        const EMPTY : &'static [Self; 1] = &[0];
-        fn debug(buf: &[Self], fmt: &mut Formatter) -> fmt::Result { crate::fmt::c16_units(buf, fmt) }
-        #[cfg(feature = "std")] fn to_string_lossy(buf: &[Self]) -> Cow<str> { Cow::Owned(String::from_utf16_lossy(buf)) }
-        fn zeroed<const N: usize>() -> [Self; N] { unsafe { core::mem::zeroed() } }
+    /// Decode `units`, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+    #[cfg(feature = "alloc")]
+    fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { Cow::Owned(decode_lossy(units).collect()) }
+}
+
+/// A [`Unit`] where any sequence of units (sans [`NUL`](Unit::NUL)) is a valid string, allowing strings to be built from raw unit slices
+/// (e.g. [`CStrPtr::from_units_with_nul`](crate::CStrPtr::from_units_with_nul), [`CStrBuf::try_set`](crate::CStrBuf::try_set).)
+///
+/// [`Utf8`](crate::Utf8) deliberately doesn't implement this: a <code>&\[[Utf8](crate::Utf8)\]</code> sliced or truncated mid-character isn't valid UTF8.
+///
+/// ### Safety
+///
+/// By implementing this trait, you promise that any sequence of valid `Self`s, terminated by [`NUL`](Unit::NUL), is a valid string of `Self`:
+/// no safe API (e.g. an `as_str` that skips validation) may rely on an invariant spanning multiple units.
+pub unsafe trait AnyUnitSequence : Unit {}
+
+unsafe impl AnyUnitSequence for u8  {}
+unsafe impl AnyUnitSequence for u16 {}
+unsafe impl AnyUnitSequence for u32 {}
+
+unsafe impl Unit for u8 {
+    type CChar = crate::c_char;
+    type StrError = crate::InteriorNulError;
+    const NUL : Self = 0;
+    const EMPTY : &'static [Self; 1] = &[0];
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "\\x{:02x}", unit) }
+    fn decode(units: &[Self]) -> (Option<char>, usize) {
+        if units[0] < 0x80 { return (Some(units[0] as char), 1); }
+        let n = units.len().min(4);
+        let valid = match core::str::from_utf8(&units[..n]) {
+            Ok(valid) => valid,
+            Err(err) if err.valid_up_to() > 0 => unsafe { core::str::from_utf8_unchecked(&units[..err.valid_up_to()]) },
+            Err(err) => return (None, err.error_len().map_or(n, usize::from)),
+        };
+        let ch = valid.chars().next().unwrap();
+        (Some(ch), ch.len_utf8())
     }
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { ch.encode_utf8(buf).as_bytes() }
+    #[cfg(feature = "alloc")] fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { String::from_utf8_lossy(units) }
+}
 
-    impl Unit for u32 {
-        type CChar = Self;
-        const NUL : Self = 0;
-        const EMPTY : &'static [Self; 1] = &[0];
-        fn debug(buf: &[Self], fmt: &mut Formatter) -> fmt::Result { crate::fmt::c32_units(buf, fmt) }
-        #[cfg(feature = "std")] fn to_string_lossy(buf: &[Self]) -> Cow<str> { Cow::Owned(buf.iter().copied().map(|ch| core::char::from_u32(ch).unwrap_or(REPLACEMENT_CHARACTER)).collect::<String>()) }
-        fn zeroed<const N: usize>() -> [Self; N] { unsafe { core::mem::zeroed() } }
+unsafe impl Unit for u16 {
+    type CChar = Self;
+    type StrError = crate::InteriorNulError;
+    const NUL : Self = 0;
+    const EMPTY : &'static [Self; 1] = &[0];
+    // Rust doesn't have a UTF16 code unit escape.  Use a C++ style "\u1234" instead of a Rust style "\u{1234}"
+    // to underscore this fact, and discourage using this in text which might need to round trip, which would
+    // fail on unpaired surrogates.
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "\\u{:04x}", unit) }
+    fn decode(units: &[Self]) -> (Option<char>, usize) {
+        match core::char::decode_utf16(units.iter().copied()).next().unwrap() {
+            Ok(ch)  => (Some(ch), ch.len_utf16()),
+            Err(_)  => (None, 1),
+        }
     }
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { ch.encode_utf16(buf) }
+    #[cfg(feature = "alloc")] fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { Cow::Owned(String::from_utf16_lossy(units)) }
+}
+
+unsafe impl Unit for u32 {
+    type CChar = Self;
+    type StrError = crate::InteriorNulError;
+    const NUL : Self = 0;
+    const EMPTY : &'static [Self; 1] = &[0];
+    fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { write!(fmt, "\\u{{{:x}}}", unit) }
+    fn decode(units: &[Self]) -> (Option<char>, usize) { (core::char::from_u32(units[0]), 1) }
+    fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] { buf[0] = ch.into(); &buf[..1] }
 }
 
+macro_rules! signed_units {
+    ( $( $signed:ty => $unsigned:ty ),* $(,)? ) => {$(
+        unsafe impl Unit for $signed {
+            type CChar = Self;
+            type StrError = crate::InteriorNulError;
+            const NUL : Self = 0;
+            const EMPTY : &'static [Self; 1] = &[0];
+            fn debug_unit(unit: Self, fmt: &mut Formatter) -> fmt::Result { <$unsigned>::debug_unit(unit as $unsigned, fmt) }
+            fn decode(units: &[Self]) -> (Option<char>, usize) { <$unsigned>::decode(Self::as_unsigned(units)) }
+            fn encode_char(ch: char, buf: &mut [Self; 4]) -> &[Self] {
+                let mut unsigned = [0; 4];
+                let n = <$unsigned>::encode_char(ch, &mut unsigned).len();
+                for (s, u) in buf.iter_mut().zip(unsigned.iter()) { *s = *u as $signed; }
+                &buf[..n]
+            }
+            #[cfg(feature = "alloc")] fn to_string_lossy(units: &[Self]) -> Cow<'_, str> { <$unsigned>::to_string_lossy(Self::as_unsigned(units)) }
+        }
+
+        unsafe impl AnyUnitSequence for $signed {}
+
+        impl AsUnsigned for $signed {
+            type Unsigned = $unsigned;
+            fn as_unsigned(units: &[Self]) -> &[$unsigned] {
+                // SAFETY: ✔️ same size, alignment, and every bit pattern is valid for both
+                unsafe { core::slice::from_raw_parts(units.as_ptr().cast(), units.len()) }
+            }
+        }
+    )*};
+}
+
+signed_units! {
+    i8  => u8,
+    i16 => u16,
+    i32 => u32,
+}
+
+/// Reinterpret signed units as their unsigned counterparts.
+trait AsUnsigned : Sized {
+    type Unsigned;
+    fn as_unsigned(units: &[Self]) -> &[Self::Unsigned];
+}
+
+pub(crate) fn is_aligned<T>(ptr: *const T) -> bool { ptr as usize % core::mem::align_of::<T>() == 0 }
+
 pub(crate) unsafe fn strlen<U: Unit>(mut str: *const U) -> usize {
+    debug_assert!(is_aligned(str), "Undefined Behavior: C string pointer {:p} isn't aligned for its unit type (consider CStrPtrUnaligned)", str);
     let mut n = 0;
     loop {
         if *str == U::NUL { return n; }
@@ -68,3 +228,21 @@ pub(crate) unsafe fn strlen<U: Unit>(mut str: *const U) -> usize {
         str = str.offset(1);
     }
 }
+
+/// Decode `units`, replacing invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][core::char::REPLACEMENT_CHARACTER].
+pub(crate) fn decode_lossy<U: Unit>(units: &[U]) -> crate::CStrCharsLossy<'_, U> { crate::CStrCharsLossy::new(units) }
+
+
+
+#[test] fn signed() {
+    let mut buf = [0i16; 4];
+    assert_eq!(i16::encode_char('\u{1F980}', &mut buf), &[0xD83Eu16 as i16, 0xDD80u16 as i16]);
+    assert_eq!(i8::decode(&[0xC3u8 as i8, 0xA9u8 as i8, 0]), (Some('\u{E9}'), 2));
+    assert_eq!(i8::decode(&[-1, 0]), (None, 1));
+    assert_eq!(i32::decode(&[-1]), (None, 1));
+
+    let s = CStrNonNull::<i8>::from_units_with_nul(&[0x61, -1, 0]).unwrap();
+    #[cfg(feature = "alloc")] assert_eq!(alloc::format!("{:?}", s), "\"a\\xff\"");
+    assert!(s.chars_lossy().eq(['a', '\u{FFFD}'].iter().copied()));
+    #[cfg(feature = "alloc")] assert_eq!(with_cstr("\u{1F980}", |s: CStrNonNull<i16>| s.to_units().len()).unwrap(), 2);
+}
@@ -0,0 +1,121 @@
+use crate::*;
+
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+
+
+/// Recursively validate or `\0`-terminate every [`CStrBuf`] in a (possibly nested) structure before passing it to C.
+///
+/// Typically implemented with `#[derive(ValidateCStr)]`, which recurses through every field that isn't marked
+/// `#[abistr(skip)]`.  Arrays, slices, and [`Option`]s recurse into their elements, while primitives, pointers, and
+/// [`CStrPtr`]/[`CStrNonNull`] (which are always `\0`-terminated) have nothing to validate.
+///
+/// ### Example
+///
+/// ```
+/// use abistr::*;
+///
+/// #[derive(ValidateCStr, Default)]
+/// #[repr(C)] struct Inner {
+///     names:  [CStrBuf<u8, 4>; 2],
+///     id:     u32,
+/// }
+///
+/// #[derive(ValidateCStr, Default)]
+/// #[repr(C)] struct Outer {
+///     inner:  Inner,
+///     #[abistr(skip)] reserved: [u8; 4],
+/// }
+///
+/// let mut outer = Outer::default();
+/// unsafe { outer.inner.names[1].buffer_mut().copy_from_slice(b"abcd") };
+/// assert_eq!(outer.validate_all().unwrap_err().to_string(), "`inner.names[1]` is not nul terminated");
+///
+/// outer.nul_truncate_all();
+/// assert_eq!(outer.validate_all(), Ok(()));
+/// assert_eq!(outer.inner.names[1].to_bytes(), b"abc");
+/// ```
+pub trait ValidateCStr {
+    /// Check that every [`CStrBuf`] within `self` is `\0`-terminated, returning the path to the first one that isn't.
+    fn validate_all(&self) -> Result<(), FieldError>;
+
+    /// Ensure every [`CStrBuf`] within `self` is `\0`-terminated, via [`CStrBuf::nul_truncate`].
+    fn nul_truncate_all(&mut self);
+}
+
+impl<U: Unit, const N: usize> ValidateCStr for CStrBuf<U, N> {
+    fn validate_all(&self) -> Result<(), FieldError> { Ok(self.validate()?) }
+    fn nul_truncate_all(&mut self) { self.nul_truncate(); }
+}
+
+impl<T: ValidateCStr> ValidateCStr for [T] {
+    fn validate_all(&self) -> Result<(), FieldError> {
+        for (i, e) in self.iter().enumerate() { e.validate_all().map_err(|err| err.with_index(i))?; }
+        Ok(())
+    }
+    fn nul_truncate_all(&mut self) { for e in self.iter_mut() { e.nul_truncate_all(); } }
+}
+
+impl<T: ValidateCStr, const N: usize> ValidateCStr for [T; N] {
+    fn validate_all(&self) -> Result<(), FieldError> { self[..].validate_all() }
+    fn nul_truncate_all(&mut self) { self[..].nul_truncate_all() }
+}
+
+impl<T: ValidateCStr> ValidateCStr for Option<T> {
+    fn validate_all(&self) -> Result<(), FieldError> { self.as_ref().map_or(Ok(()), T::validate_all) }
+    fn nul_truncate_all(&mut self) { if let Some(t) = self { t.nul_truncate_all() } }
+}
+
+macro_rules! nothing_to_validate {
+    ( $( impl $(<$($gen:ident $(: $bound:path)?),*>)? for $ty:ty ),* $(,)? ) => {$(
+        impl $(<$($gen $(: $bound)?),*>)? ValidateCStr for $ty {
+            fn validate_all(&self) -> Result<(), FieldError> { Ok(()) }
+            fn nul_truncate_all(&mut self) {}
+        }
+    )*};
+}
+
+nothing_to_validate! {
+    impl for (), impl for bool, impl for char, impl for f32, impl for f64,
+    impl for u8, impl for u16, impl for u32, impl for u64, impl for u128, impl for usize,
+    impl for i8, impl for i16, impl for i32, impl for i64, impl for i128, impl for isize,
+    impl<T> for *const T, impl<T> for *mut T, impl<T> for NonNull<T>, impl<T> for PhantomData<T>,
+    impl<U: Unit> for CStrPtr<'_, U>, impl<U: Unit> for CStrNonNull<'_, U>,
+}
+
+
+
+#[test] fn nested() {
+    #[derive(ValidateCStr, Default)]
+    struct Leaf<T: Default + ValidateCStr, const N: usize>(CStrBuf<u16, N>, T);
+
+    #[derive(ValidateCStr, Default)]
+    struct Branch<'s> {
+        leaves:     [Leaf<u8, 2>; 3],
+        opt:        Option<Leaf<CStrBuf<u8, 2>, 4>>,
+        ptr:        CStrPtr<'s>,
+        #[abistr(skip)] unchecked: CStrBuf<u8, 1>,
+    }
+
+    let mut b = Branch::default();
+    assert_eq!(b.validate_all(), Ok(()));
+
+    unsafe { b.unchecked.buffer_mut()[0] = b'a' };
+    assert_eq!(b.validate_all(), Ok(()));
+
+    unsafe { b.leaves[2].0.buffer_mut().copy_from_slice(&[1, 2]) };
+    let err = b.validate_all().unwrap_err();
+    assert_eq!(err.path(), &[FieldPathSegment::Field("leaves"), FieldPathSegment::Index(2), FieldPathSegment::Field("0")]);
+
+    b.opt = Some(Leaf(CStrBuf::default(), CStrBuf::default()));
+    unsafe { b.opt.as_mut().unwrap().1.buffer_mut().copy_from_slice(b"ab") };
+    b.nul_truncate_all();
+    assert_eq!(b.validate_all(), Ok(()));
+    assert_eq!(b.leaves[2].0.to_units(), &[1]);
+    assert_eq!(b.opt.as_ref().unwrap().1.to_bytes(), b"a");
+    assert_eq!(b.unchecked.buffer(), b"a"); // skipped
+
+    unsafe { b.opt.as_mut().unwrap().1.buffer_mut().copy_from_slice(b"ab") };
+    assert_eq!(b.validate_all().unwrap_err().path(), &[FieldPathSegment::Field("opt"), FieldPathSegment::Field("1")]);
+}
@@ -0,0 +1,209 @@
+use crate::*;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter, Write};
+
+
+
+/// An owned [WTF-8](https://simonsapin.github.io/wtf-8/) string: UTF8, extended to losslessly represent ill-formed UTF16 (unpaired surrogates.)
+///
+/// Unlike <code>[CStrPtr]<[u16]>::[to_string_lossy](CStrPtr::to_string_lossy)</code>, which replaces unpaired surrogates with
+/// [`U+FFFD REPLACEMENT CHARACTER`](core::char::REPLACEMENT_CHARACTER), converting to [`Wtf8Buf`] and back round trips exactly - handy
+/// for filenames and identifiers which must be stored, compared, and handed back to C unchanged.
+///
+/// ### Example
+///
+/// ```
+/// # use abistr::*;
+/// let units = [0x61, 0xD800, 0x62, 0];
+/// let wide = CStrNonNull::<u16>::from_units_with_nul(&units).unwrap();
+/// let wtf8 = wide.to_wtf8();
+/// assert_eq!(wtf8.as_str(), None); // not valid UTF8
+/// assert_eq!(wtf8.to_string_lossy(), "a\u{FFFD}b");
+///
+/// let mut buf = CStrBuf::<u16, 8>::default();
+/// buf.try_set_wtf8(&wtf8).unwrap();
+/// assert_eq!(buf.to_units(), &units[..3]);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wtf8Buf(Vec<u8>); // always valid WTF-8
+
+impl Wtf8Buf {
+    /// Create an empty string.
+    pub fn new() -> Self { Self(Vec::new()) }
+
+    /// Losslessly convert (potentially ill-formed) UTF16 `units`.
+    pub fn from_wide(units: &[u16]) -> Self {
+        let mut bytes = Vec::with_capacity(units.len());
+        for ch in core::char::decode_utf16(units.iter().copied()) {
+            match ch {
+                Ok(ch) => bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+                Err(err) => {
+                    let unit = err.unpaired_surrogate();
+                    bytes.extend_from_slice(&[0xE0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]);
+                },
+            }
+        }
+        Self(bytes)
+    }
+
+    /// The WTF-8 bytes of the string (no terminal `\0`.)
+    pub fn as_bytes(&self) -> &[u8] { &self.0 }
+
+    /// Borrow the string as a [`str`], or return [`None`] if it contains unpaired surrogates.
+    pub fn as_str(&self) -> Option<&str> { core::str::from_utf8(&self.0).ok() }
+
+    /// Convert the string into a [`String`], or return <code>[Err]\(self\)</code> if it contains unpaired surrogates.
+    pub fn into_string(self) -> Result<String, Self> { String::from_utf8(self.0).map_err(|err| Self(err.into_bytes())) }
+
+    /// Convert the string into a [`str`], replacing unpaired surrogates with [`U+FFFD REPLACEMENT CHARACTER`](core::char::REPLACEMENT_CHARACTER).
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self.as_str() {
+            Some(s) => Cow::Borrowed(s),
+            None    => Cow::Owned(self.code_points().map(|cp| core::char::from_u32(cp).unwrap_or(core::char::REPLACEMENT_CHARACTER)).collect()),
+        }
+    }
+
+    /// Encode the string back into the exact UTF16 it was created from.
+    pub fn encode_wide(&self) -> Wtf8EncodeWide<'_> { Wtf8EncodeWide { bytes: &self.0, low: None } }
+
+    /// Encode the string back into UTF16 + `\0`.  Fails if the string contains any `\0`s.
+    pub fn to_small_cstring(&self) -> Result<SmallCString<u16>, InteriorNulError> { SmallCString::from_unit_iter(self.encode_wide()) }
+
+    fn code_points(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut bytes = &self.0[..];
+        core::iter::from_fn(move || {
+            let (cp, rest) = next_code_point(bytes)?;
+            bytes = rest;
+            Some(cp)
+        })
+    }
+}
+
+/// Decode the next code point (which may be a surrogate) of valid WTF-8.
+fn next_code_point(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let (&lead, rest) = bytes.split_first()?;
+    let (n, init) = match lead {
+        0x00 ..= 0x7F   => return Some((u32::from(lead), rest)),
+        0x80 ..= 0xDF   => (1, u32::from(lead & 0x1F)),
+        0xE0 ..= 0xEF   => (2, u32::from(lead & 0x0F)),
+        0xF0 ..= 0xFF   => (3, u32::from(lead & 0x07)),
+    };
+    let cp = rest[..n].iter().fold(init, |cp, b| (cp << 6) | u32::from(b & 0x3F));
+    Some((cp, &rest[n..]))
+}
+
+impl From<&str>  for Wtf8Buf { fn from(s: &str ) -> Self { Self(s.as_bytes().to_vec()) } }
+impl From<String> for Wtf8Buf { fn from(s: String) -> Self { Self(s.into_bytes()) } }
+
+impl Display for Wtf8Buf { fn fmt(&self, fmt: &mut Formatter) -> fmt::Result { Display::fmt(&self.to_string_lossy(), fmt) } }
+impl Debug for Wtf8Buf {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.write_char('"')?;
+        for cp in self.code_points() {
+            match core::char::from_u32(cp) {
+                Some(ch)    => ch.escape_debug().try_for_each(|ch| fmt.write_char(ch))?,
+                None        => write!(fmt, "\\u{:04x}", cp)?, // lone surrogate, escaped like `u16` strings
+            }
+        }
+        fmt.write_char('"')
+    }
+}
+
+
+
+/// Iterator over the UTF16 units of a [`Wtf8Buf`].  Returned by [`Wtf8Buf::encode_wide`].
+#[derive(Clone)]
+pub struct Wtf8EncodeWide<'s> {
+    bytes:  &'s [u8],
+    low:    Option<u16>,
+}
+
+impl Iterator for Wtf8EncodeWide<'_> {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        if let Some(low) = self.low.take() { return Some(low) }
+        let (cp, rest) = next_code_point(self.bytes)?;
+        self.bytes = rest;
+        if cp < 0x10000 { return Some(cp as u16) }
+        let cp = cp - 0x10000;
+        self.low = Some(0xDC00 | (cp & 0x3FF) as u16);
+        Some(0xD800 | (cp >> 10) as u16)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let low = usize::from(self.low.is_some());
+        ((self.bytes.len() + 2) / 3 + low, Some(self.bytes.len() + low))
+    }
+}
+
+impl core::iter::FusedIterator for Wtf8EncodeWide<'_> {}
+
+
+
+impl CStrPtr<'_, u16> {
+    /// Losslessly convert the (potentially ill-formed) string to [WTF-8](Wtf8Buf).
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn to_wtf8(&self) -> Wtf8Buf { Wtf8Buf::from_wide(self.to_units()) }
+}
+
+impl CStrNonNull<'_, u16> {
+    /// Losslessly convert the (potentially ill-formed) string to [WTF-8](Wtf8Buf).
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn to_wtf8(&self) -> Wtf8Buf { Wtf8Buf::from_wide(self.to_units()) }
+}
+
+impl<const N: usize> CStrBuf<u16, N> {
+    /// Losslessly convert the (potentially ill-formed) buffer to [WTF-8](Wtf8Buf).
+    ///
+    /// `O(n)` to locate the terminal `\0`.
+    pub fn to_wtf8(&self) -> Wtf8Buf { Wtf8Buf::from_wide(self.to_units()) }
+
+    /// Modifies the buffer to contain `data` (encoded back into the exact UTF16 it was created from) + `\0`.
+    /// If `data` contains a `\0`, or `data` + `\0` will not fit, <code>[Err]\([SetStrError]\)</code> will be returned without modifying the underlying buffer.
+    pub fn try_set_wtf8(&mut self, data: &Wtf8Buf) -> Result<(), SetStrError> {
+        if let Some(nul) = data.encode_wide().position(|unit| unit == 0) { Err(InteriorNulError::new(nul))? }
+        let required = data.encode_wide().count() + 1;
+        if required > N { Err(BufferTooSmallError::new(required, N))? }
+        let mut buffer = [0u16; N];
+        for (dst, unit) in buffer.iter_mut().zip(data.encode_wide()) { *dst = unit; }
+        Ok(self.try_set(&buffer[..required-1])?)
+    }
+}
+
+
+
+#[test] fn wtf8() {
+    use alloc::format;
+
+    let units = [0x61, 0xD800, 0x62, 0xDC00, 0xD83E, 0xDD80, 0xDBFF, 0];
+    let wide = CStrNonNull::<u16>::from_units_with_nul(&units).unwrap();
+    let wtf8 = wide.to_wtf8();
+    assert_eq!(wtf8.as_bytes(), b"a\xED\xA0\x80b\xED\xB0\x80\xF0\x9F\xA6\x80\xED\xAF\xBF");
+    assert_eq!(wtf8.encode_wide().collect::<Vec<_>>(), &units[..7]);
+    assert_eq!(wtf8.to_string_lossy(), wide.to_string_lossy());
+    assert_eq!(format!("{:?}", wtf8), "\"a\\ud800b\\udc00\u{1F980}\\udbff\"");
+    assert_eq!(format!("{}", wtf8), "a\u{FFFD}b\u{FFFD}\u{1F980}\u{FFFD}");
+    assert_eq!(wtf8.to_small_cstring().unwrap().to_units_with_nul(), &units[..]);
+    assert_eq!(wtf8.clone().into_string(), Err(wtf8.clone()));
+    assert_eq!(CStrPtr::<u16>::from_units_with_nul(&units).unwrap().to_wtf8(), wtf8);
+
+    let mut buf = CStrBuf::<u16, 8>::default();
+    buf.try_set_wtf8(&wtf8).unwrap();
+    assert_eq!(buf.to_units(), &units[..7]);
+    assert_eq!(buf.to_wtf8(), wtf8);
+    assert_eq!(CStrBuf::<u16, 7>::default().try_set_wtf8(&wtf8), Err(BufferTooSmallError::new(8, 7).into()));
+    assert_eq!(buf.try_set_wtf8(&Wtf8Buf::from_wide(&[0x61, 0, 0xD800])), Err(InteriorNulError::new(1).into()));
+    assert_eq!(buf.to_units(), &units[..7], "failures shouldn't modify the buffer");
+
+    let valid = Wtf8Buf::from("a\u{1F980}");
+    assert_eq!(valid, Wtf8Buf::from_wide(&[0x61, 0xD83E, 0xDD80]));
+    assert_eq!(valid.as_str(), Some("a\u{1F980}"));
+    assert_eq!(valid.clone().into_string().as_deref(), Ok("a\u{1F980}"));
+    assert_eq!(Wtf8Buf::from("a\0b").to_small_cstring().unwrap_err(), InteriorNulError::new(1));
+    assert!(Wtf8Buf::from_wide(&[0xD800]) < Wtf8Buf::from_wide(&[0xDC00]));
+    assert_eq!(Wtf8Buf::new().encode_wide().count(), 0);
+}